@@ -1,16 +1,37 @@
+use std::ops::Deref;
+
 use anchor_lang::{
     prelude::*,
-    solana_program::program::invoke,
-    system_program::{create_account, CreateAccount},
+    solana_program::{program::invoke_signed, program_option::COption},
+    system_program::{self, create_account, CreateAccount},
 };
 use anchor_spl::{
     associated_token,
-    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+    token::{self, Approve, CloseAccount, Mint, MintTo, Revoke, Token, TokenAccount, Transfer},
+};
+use mpl_token_metadata::{
+    instruction as token_instruction, state::TokenMetadataAccount, ID as TOKEN_METADATA_ID,
 };
-use mpl_token_metadata::{instruction as token_instruction, ID as TOKEN_METADATA_ID};
 
 declare_id!("7ghLrtu6EqZuRcNQX5cvWp8THJ6tgfbSXEAKZ8GhVRy4");
 
+pub const PROGRAM_AUTHORITY_SEED: &[u8] = b"authority";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const LISTING_SEED: &[u8] = b"listing";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const AUCTION_SEED: &[u8] = b"auction";
+pub const AUCTION_ESCROW_SEED: &[u8] = b"auction_escrow";
+pub const BID_VAULT_SEED: &[u8] = b"bid_vault";
+pub const DUTCH_SALE_SEED: &[u8] = b"dutch_sale";
+pub const DUTCH_VAULT_SEED: &[u8] = b"dutch_vault";
+pub const DUTCH_RECEIPT_SEED: &[u8] = b"dutch_receipt";
+pub const BATCH_MINT_SEED: &[u8] = b"batch_mint";
+
+// Every NFT of a batch adds 4 accounts, 10 NFTs and the shared accounts stay below the
+// 64 accounts a transaction can lock. Full batches need a v0 transaction with a lookup table.
+pub const MAX_BATCH_SIZE: usize = 10;
+
 mod mpl_simplified_methods {
     use super::*;
 
@@ -20,35 +41,162 @@ mod mpl_simplified_methods {
         minter_account: AccountInfo<'info>,
         token_program: AccountInfo<'info>,
     ) -> Result<()> {
+        _create_account_signed(
+            system_program,
+            owner_account,
+            minter_account,
+            token_program,
+            &[],
+        )
+    }
+
+    pub fn _create_account_signed<'info>(
+        system_program: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        // The mint has to be rent exempt, so we fund exactly the minimum balance for its size
+        let lamports = Rent::get()?.minimum_balance(Mint::LEN);
+
         create_account(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 // We'll use the system program to create account, but token_program also has the create_account function
                 system_program.clone(), // Target program
                 CreateAccount {
                     from: owner_account.clone(), // From pubkey
                     to: minter_account.clone(),  // To pubkey
                 },
+                signer_seeds,
             ),
-            10000000,             // Lamports (1 SOL)
-            82,                   // Space (has to be exact, idk where this value comes from)
+            lamports,             // Lamports (Rent exempt minimum)
+            Mint::LEN as u64,     // Space (Size of a SPL Token mint, 82 bytes)
             &token_program.key(), // Owner
         )?;
         Ok(())
     }
 
+    /// Creates a PDA owned by this program, for accounts that are only created in some cases and
+    /// so can't use `init`. Someone may have sent lamports to the address already, which makes
+    /// create_account fail, so that case is topped up, allocated and assigned like Anchor does.
+    pub fn _create_program_account_signed<'info>(
+        system_program: AccountInfo<'info>,
+        payer_account: AccountInfo<'info>,
+        new_account: AccountInfo<'info>,
+        space: usize,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let lamports = Rent::get()?.minimum_balance(space);
+
+        if new_account.lamports() == 0 {
+            create_account(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    CreateAccount {
+                        from: payer_account.clone(),
+                        to: new_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                lamports,
+                space as u64,
+                &crate::ID,
+            )?;
+            return Ok(());
+        }
+
+        let missing_lamports = lamports.saturating_sub(new_account.lamports());
+        if missing_lamports > 0 {
+            _transfer_lamports(
+                system_program.clone(),
+                payer_account.clone(),
+                new_account.clone(),
+                missing_lamports,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: new_account.clone(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: new_account.clone(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+        Ok(())
+    }
+
+    pub fn _transfer_lamports<'info>(
+        system_program: AccountInfo<'info>,
+        from_account: AccountInfo<'info>,
+        to_account: AccountInfo<'info>,
+        lamports: u64,
+    ) -> Result<()> {
+        _transfer_lamports_signed(system_program, from_account, to_account, lamports, &[])
+    }
+
+    pub fn _transfer_lamports_signed<'info>(
+        system_program: AccountInfo<'info>,
+        from_account: AccountInfo<'info>,
+        to_account: AccountInfo<'info>,
+        lamports: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                system_program.clone(), // Target program
+                system_program::Transfer {
+                    from: from_account.clone(), // From pubkey
+                    to: to_account.clone(),     // To pubkey
+                },
+                signer_seeds,
+            ),
+            lamports,
+        )
+    }
+
     pub fn _initialize_mint_account<'info>(
         owner_account: AccountInfo<'info>,
         minter_account: AccountInfo<'info>,
         token_program: AccountInfo<'info>,
         rent_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        _initialize_mint_account_signed(
+            owner_account,
+            minter_account,
+            token_program,
+            rent_program,
+            &[],
+        )
+    }
+
+    pub fn _initialize_mint_account_signed<'info>(
+        owner_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        rent_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
     ) -> Result<()> {
         token::initialize_mint(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 token_program.clone(), // Target program
                 token::InitializeMint {
                     mint: minter_account.clone(), // Mint pubkey
                     rent: rent_program.clone(),   // Rent pubkey
                 },
+                signer_seeds,
             ),
             0,                          // decimals
             &owner_account.key(),       // mint authority
@@ -66,10 +214,35 @@ mod mpl_simplified_methods {
         rent_program: AccountInfo<'info>,
         system_program: AccountInfo<'info>,
     ) -> Result<()> {
-        associated_token::create(CpiContext::new(
+        _initialize_token_holder_account_signed(
+            owner_account.clone(),
+            owner_account,
+            mint_account,
+            token_holder_account,
+            associated_token_program,
+            token_program,
+            rent_program,
+            system_program,
+            &[],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn _initialize_token_holder_account_signed<'info>(
+        owner_account: AccountInfo<'info>,
+        payer_account: AccountInfo<'info>,
+        mint_account: AccountInfo<'info>,
+        token_holder_account: AccountInfo<'info>,
+        associated_token_program: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        rent_program: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        associated_token::create(CpiContext::new_with_signer(
             associated_token_program.clone(), // Target program
             associated_token::Create {
-                payer: owner_account.clone(),                   // Payer pubkey
+                payer: payer_account.clone(),                   // Payer pubkey
                 associated_token: token_holder_account.clone(), // Associated token pubkey
                 authority: owner_account.clone(),               // Authority pubkey
                 mint: mint_account.clone(),                     // Mint pubkey
@@ -77,75 +250,446 @@ mod mpl_simplified_methods {
                 token_program: token_program.clone(),           // Token program pubkey
                 rent: rent_program.clone(),                     // Rent program pubkey
             },
+            signer_seeds,
+        ))
+    }
+
+    pub fn _transfer_token<'info>(
+        token_program: AccountInfo<'info>,
+        from_account: AccountInfo<'info>,
+        to_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        _transfer_token_signed(
+            token_program,
+            from_account,
+            to_account,
+            authority_account,
+            amount,
+            &[],
+        )
+    }
+
+    pub fn _transfer_token_signed<'info>(
+        token_program: AccountInfo<'info>,
+        from_account: AccountInfo<'info>,
+        to_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(), // Target program
+                Transfer {
+                    from: from_account.clone(),           // From token account
+                    to: to_account.clone(),               // To token account
+                    authority: authority_account.clone(), // Owner or delegate of the from account
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    pub fn _close_token_account_signed<'info>(
+        token_program: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        destination_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        token::close_account(CpiContext::new_with_signer(
+            token_program.clone(), // Target program
+            CloseAccount {
+                account: token_account.clone(),           // Emptied token account
+                destination: destination_account.clone(), // Receives the rent
+                authority: authority_account.clone(),     // Owner of the token account
+            },
+            signer_seeds,
+        ))
+    }
+
+    pub fn _approve_token_delegate<'info>(
+        token_program: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        delegate_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        _approve_token_delegate_signed(
+            token_program,
+            token_account,
+            delegate_account,
+            owner_account,
+            amount,
+            &[],
+        )
+    }
+
+    pub fn _approve_token_delegate_signed<'info>(
+        token_program: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        delegate_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        token::approve(
+            CpiContext::new_with_signer(
+                token_program.clone(), // Target program
+                Approve {
+                    to: token_account.clone(),          // Token account to delegate
+                    delegate: delegate_account.clone(), // Delegate pubkey
+                    authority: owner_account.clone(),   // Owner of the token account
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    pub fn _revoke_token_delegate<'info>(
+        token_program: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+    ) -> Result<()> {
+        _revoke_token_delegate_signed(token_program, token_account, owner_account, &[])
+    }
+
+    pub fn _revoke_token_delegate_signed<'info>(
+        token_program: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        token::revoke(CpiContext::new_with_signer(
+            token_program.clone(), // Target program
+            Revoke {
+                source: token_account.clone(),    // Delegated token account
+                authority: owner_account.clone(), // Owner of the token account
+            },
+            signer_seeds,
         ))
     }
 
+    pub fn _freeze_delegated_account<'info>(
+        delegate_account: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        _freeze_delegated_account_signed(
+            delegate_account,
+            token_account,
+            edition_account,
+            minter_account,
+            token_program,
+            &[],
+        )
+    }
+
+    pub fn _freeze_delegated_account_signed<'info>(
+        delegate_account: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        // The edition is the freeze authority of the mint, Token Metadata freezes on behalf of the delegate
+        invoke_signed(
+            &token_instruction::freeze_delegated_account(
+                TOKEN_METADATA_ID,              // Target Program Address
+                delegate_account.clone().key(), // Delegate Account
+                token_account.clone().key(),    // Token Account
+                edition_account.clone().key(),  // Edition Account
+                minter_account.clone().key(),   // Minter Account
+            ),
+            &[
+                delegate_account.clone(), // Delegate Account
+                token_account.clone(),    // Token Account
+                edition_account.clone(),  // Edition Account
+                minter_account.clone(),   // Minter Account
+                token_program.clone(),    // Token Program
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn _thaw_delegated_account<'info>(
+        delegate_account: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        _thaw_delegated_account_signed(
+            delegate_account,
+            token_account,
+            edition_account,
+            minter_account,
+            token_program,
+            &[],
+        )
+    }
+
+    pub fn _thaw_delegated_account_signed<'info>(
+        delegate_account: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        invoke_signed(
+            &token_instruction::thaw_delegated_account(
+                TOKEN_METADATA_ID,              // Target Program Address
+                delegate_account.clone().key(), // Delegate Account
+                token_account.clone().key(),    // Token Account
+                edition_account.clone().key(),  // Edition Account
+                minter_account.clone().key(),   // Minter Account
+            ),
+            &[
+                delegate_account.clone(), // Delegate Account
+                token_account.clone(),    // Token Account
+                edition_account.clone(),  // Edition Account
+                minter_account.clone(),   // Minter Account
+                token_program.clone(),    // Token Program
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    /// Pays every creator their share of the royalty out of `price` and returns the split
+    /// together with what is left for the seller. `creator_accounts` follow the metadata creators order.
+    /// A share too small to make an empty creator wallet rent exempt is left to the seller,
+    /// the runtime would reject the sale otherwise.
+    pub fn _pay_royalties<'info>(
+        system_program: AccountInfo<'info>,
+        payer_account: AccountInfo<'info>,
+        metadata: &mpl_token_metadata::state::Metadata,
+        creator_accounts: &[AccountInfo<'info>],
+        price: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<(Vec<RoyaltySplit>, u64)> {
+        let creators = metadata.data.creators.as_deref().unwrap_or_default();
+        require!(
+            creator_accounts.len() == creators.len(),
+            ErrorCode::MissingCreatorAccounts
+        );
+
+        // u128 so price * basis points can't overflow
+        let royalty =
+            (price as u128 * metadata.data.seller_fee_basis_points as u128 / 10000) as u64;
+
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+
+        let mut splits = Vec::with_capacity(creators.len());
+        for (creator, creator_account) in creators.iter().zip(creator_accounts) {
+            require_keys_eq!(
+                creator_account.key(),
+                creator.address,
+                ErrorCode::InvalidCreatorAccount
+            );
+
+            // Rounding dust stays with the seller
+            let mut amount = (royalty as u128 * creator.share as u128 / 100) as u64;
+            if creator_account.lamports() == 0 && amount < rent_exempt_minimum {
+                amount = 0;
+            }
+            if amount > 0 {
+                _transfer_lamports_signed(
+                    system_program.clone(),
+                    payer_account.clone(),
+                    creator_account.clone(),
+                    amount,
+                    signer_seeds,
+                )?;
+            }
+            splits.push(RoyaltySplit {
+                creator: creator.address,
+                amount,
+            });
+        }
+
+        let royalties_paid: u64 = splits.iter().map(|split| split.amount).sum();
+        Ok((splits, price - royalties_paid))
+    }
+
     pub fn _mint_token_to_account<'info>(
         minter_account: AccountInfo<'info>,
         token_holder_account: AccountInfo<'info>,
         owner_account: AccountInfo<'info>,
         token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        _mint_token_to_account_signed(
+            minter_account,
+            token_holder_account,
+            owner_account,
+            token_program,
+            &[],
+        )
+    }
+
+    pub fn _mint_token_to_account_signed<'info>(
+        minter_account: AccountInfo<'info>,
+        token_holder_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
     ) -> Result<()> {
         // Minting Token
         token::mint_to(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 token_program.clone(),
                 MintTo {
                     mint: minter_account.clone(),
                     to: token_holder_account.clone(),
                     authority: owner_account.clone(),
                 },
+                signer_seeds,
             ),
             1,
         )
     }
 
+    pub fn _validate_metadata_title(metadata_title: &str) -> Result<()> {
+        require!(!metadata_title.is_empty(), ErrorCode::EmptyMetadataTitle);
+        require!(
+            metadata_title.len() <= mpl_token_metadata::state::MAX_NAME_LENGTH,
+            ErrorCode::MetadataTitleTooLong
+        );
+        Ok(())
+    }
+
+    pub fn _validate_metadata_symbol(metadata_symbol: &str) -> Result<()> {
+        // An empty symbol is fine, plenty of collections don't have one
+        require!(
+            metadata_symbol.len() <= mpl_token_metadata::state::MAX_SYMBOL_LENGTH,
+            ErrorCode::MetadataSymbolTooLong
+        );
+        Ok(())
+    }
+
+    pub fn _validate_metadata_uri(metadata_uri: &str) -> Result<()> {
+        require!(!metadata_uri.is_empty(), ErrorCode::EmptyMetadataUri);
+        require!(
+            metadata_uri.len() <= mpl_token_metadata::state::MAX_URI_LENGTH,
+            ErrorCode::MetadataUriTooLong
+        );
+        require!(
+            ["https://", "http://", "ipfs://", "ar://"]
+                .iter()
+                .any(|scheme| metadata_uri.starts_with(scheme)),
+            ErrorCode::InvalidMetadataUriScheme
+        );
+        Ok(())
+    }
+
+    pub fn _validate_creators(creators: &[NFTCreator]) -> Result<()> {
+        require!(
+            creators.len() <= mpl_token_metadata::state::MAX_CREATOR_LIMIT,
+            ErrorCode::TooManyCreators
+        );
+
+        let total_share = creators
+            .iter()
+            .try_fold(0u8, |total, creator| total.checked_add(creator.share));
+        require!(total_share == Some(100), ErrorCode::InvalidCreatorShares);
+        Ok(())
+    }
+
+    pub fn _build_creators(
+        creators: Vec<NFTCreator>,
+        authority: Pubkey,
+        existing_creators: Option<&[mpl_token_metadata::state::Creator]>,
+    ) -> Result<Vec<mpl_token_metadata::state::Creator>> {
+        _validate_creators(&creators)?;
+
+        // The signing authority is also the update authority, so Token Metadata lets us
+        // mark it as verified right away. Everyone else has to sign the metadata themselves,
+        // and Token Metadata rejects any change to a verification it did not witness, so
+        // existing flags are carried over as they are.
+        Ok(creators
+            .into_iter()
+            .map(|creator| mpl_token_metadata::state::Creator {
+                address: creator.address,
+                verified: creator.address == authority
+                    || existing_creators
+                        .into_iter()
+                        .flatten()
+                        .any(|existing| existing.address == creator.address && existing.verified),
+                share: creator.share,
+            })
+            .collect())
+    }
+
     pub fn _create_metadata_account<'info>(
         metadata_account: AccountInfo<'info>,
         minter_account: AccountInfo<'info>,
         authority_account: AccountInfo<'info>,
-        metadata_title: String,
-        metadata_symbol: String,
-        metadata_uri: String,
+        metadata_data: mpl_token_metadata::state::DataV2,
+        collection_details: Option<mpl_token_metadata::state::CollectionDetails>,
     ) -> Result<()> {
-        let creator = vec![
-            mpl_token_metadata::state::Creator {
-                address: minter_account.clone().key(),
-                verified: false,
-                share: 100,
-            },
-            mpl_token_metadata::state::Creator {
-                address: authority_account.clone().key(),
-                verified: false,
-                share: 0,
-            },
-        ];
+        _create_metadata_account_signed(
+            metadata_account,
+            minter_account,
+            authority_account.clone(),
+            authority_account,
+            metadata_data,
+            collection_details,
+            &[],
+        )
+    }
+
+    pub fn _create_metadata_account_signed<'info>(
+        metadata_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        payer_account: AccountInfo<'info>,
+        metadata_data: mpl_token_metadata::state::DataV2,
+        collection_details: Option<mpl_token_metadata::state::CollectionDetails>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        require!(
+            metadata_data.seller_fee_basis_points <= 10000,
+            ErrorCode::InvalidSellerFeeBasisPoints
+        );
 
         // Minting Metadata
-        invoke(
+        invoke_signed(
             &token_instruction::create_metadata_accounts_v3(
-                TOKEN_METADATA_ID,               // Target Program Address
-                metadata_account.clone().key(),  // Metadata Account
-                minter_account.clone().key(),    // Minter Account
-                authority_account.clone().key(), // Authority Account
-                authority_account.clone().key(), // Payer Account
-                authority_account.clone().key(), // Update Authority Account
-                metadata_title,                  // Metadata Title
-                metadata_symbol,                 // Metadata Symbol
-                metadata_uri,                    // Metadata URI
-                Some(creator),                   // Creators
-                1,                               // Seller Fee Basis Points
-                true,                            // Update Authority is Signer
-                true,                            // Is Mutable
-                None,                            // Collection
-                None,                            // Uses
-                None,                            // Collection Details
+                TOKEN_METADATA_ID,                     // Target Program Address
+                metadata_account.clone().key(),        // Metadata Account
+                minter_account.clone().key(),          // Minter Account
+                authority_account.clone().key(),       // Authority Account
+                payer_account.clone().key(),           // Payer Account
+                authority_account.clone().key(),       // Update Authority Account
+                metadata_data.name,                    // Metadata Title
+                metadata_data.symbol,                  // Metadata Symbol
+                metadata_data.uri,                     // Metadata URI
+                metadata_data.creators,                // Creators
+                metadata_data.seller_fee_basis_points, // Seller Fee Basis Points
+                true,                                  // Update Authority is Signer
+                true,                                  // Is Mutable
+                metadata_data.collection,              // Collection
+                metadata_data.uses,                    // Uses
+                collection_details,                    // Collection Details
             ),
             &[
                 metadata_account.clone(),  // Metadata Account
                 minter_account.clone(),    // Minter Account
                 authority_account.clone(), // Authority Account
+                payer_account.clone(),     // Payer Account
             ],
+            signer_seeds,
         )?;
 
         Ok(())
@@ -158,8 +702,34 @@ mod mpl_simplified_methods {
         metadata_account: AccountInfo<'info>,
         authority_account: AccountInfo<'info>,
         rent_program: AccountInfo<'info>,
+        max_supply: Option<u64>,
+    ) -> Result<()> {
+        _create_master_edition_account_signed(
+            master_edition,
+            minter_account,
+            token_holder_account,
+            metadata_account,
+            authority_account.clone(),
+            authority_account,
+            rent_program,
+            max_supply,
+            &[],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn _create_master_edition_account_signed<'info>(
+        master_edition: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_holder_account: AccountInfo<'info>,
+        metadata_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        payer_account: AccountInfo<'info>,
+        rent_program: AccountInfo<'info>,
+        max_supply: Option<u64>,
+        signer_seeds: &[&[&[u8]]],
     ) -> Result<()> {
-        invoke(
+        invoke_signed(
             &token_instruction::create_master_edition_v3(
                 TOKEN_METADATA_ID,               // Target Program Address
                 master_edition.clone().key(),    // Master Edition Account
@@ -167,8 +737,8 @@ mod mpl_simplified_methods {
                 authority_account.clone().key(), // Update Account
                 authority_account.clone().key(), // Mint Account
                 metadata_account.clone().key(),  // Metadata Account
-                authority_account.clone().key(), // Payer Account
-                Some(1),                         // Max Supply
+                payer_account.clone().key(),     // Payer Account
+                max_supply,                      // Max Supply (None = Unlimited)
             ),
             &[
                 master_edition.clone(),       // Master Edition Account
@@ -176,61 +746,610 @@ mod mpl_simplified_methods {
                 minter_account.clone(),       // Minter Account
                 token_holder_account.clone(), // Token Holder Account
                 authority_account.clone(),    // Authority Account
+                payer_account.clone(),        // Payer Account
                 rent_program.clone(),         // Rent Account
             ],
+            signer_seeds,
         )?;
 
         Ok(())
     }
 
-    pub fn _update_metadata_account<'info>(
-        metadata_account: AccountInfo<'info>,
+    #[allow(clippy::too_many_arguments)]
+    pub fn _mint_new_edition_from_master_edition<'info>(
+        edition_metadata_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        edition_minter_account: AccountInfo<'info>,
+        edition_marker_account: AccountInfo<'info>,
+        master_edition: AccountInfo<'info>,
+        master_minter_account: AccountInfo<'info>,
+        master_metadata_account: AccountInfo<'info>,
+        master_token_holder_account: AccountInfo<'info>,
         authority_account: AccountInfo<'info>,
-        metadata_title: String,
-        metadata_symbol: String,
-        metadata_uri: String,
+        edition_number: u64,
     ) -> Result<()> {
-        invoke(
-            &token_instruction::update_metadata_accounts_v2(
-                TOKEN_METADATA_ID,               // Target Program Address
-                metadata_account.clone().key(),  // Metadata Account
-                authority_account.clone().key(), // Payer Account
-                None,                            // Update Authority Account
-                Some(mpl_token_metadata::state::DataV2 {
-                    name: metadata_title,
-                    symbol: metadata_symbol,
-                    uri: metadata_uri,
-                    seller_fee_basis_points: 1,
-                    creators: None,
-                    collection: None,
-                    uses: None,
-                }), // Data
-                None,                            // Primary Sale
-                None,                            // Is Mutable
+        _mint_new_edition_from_master_edition_signed(
+            edition_metadata_account,
+            edition_account,
+            edition_minter_account,
+            edition_marker_account,
+            master_edition,
+            master_minter_account,
+            master_metadata_account,
+            master_token_holder_account,
+            authority_account,
+            edition_number,
+            &[],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn _mint_new_edition_from_master_edition_signed<'info>(
+        edition_metadata_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        edition_minter_account: AccountInfo<'info>,
+        edition_marker_account: AccountInfo<'info>,
+        master_edition: AccountInfo<'info>,
+        master_minter_account: AccountInfo<'info>,
+        master_metadata_account: AccountInfo<'info>,
+        master_token_holder_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        edition_number: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        invoke_signed(
+            &token_instruction::mint_new_edition_from_master_edition_via_token(
+                TOKEN_METADATA_ID,                         // Target Program Address
+                edition_metadata_account.clone().key(),    // Edition Metadata Account
+                edition_account.clone().key(),             // Edition Account
+                master_edition.clone().key(),              // Master Edition Account
+                edition_minter_account.clone().key(),      // Edition Minter Account
+                authority_account.clone().key(),           // Edition Mint Authority Account
+                authority_account.clone().key(),           // Payer Account
+                authority_account.clone().key(),           // Master Token Owner Account
+                master_token_holder_account.clone().key(), // Master Token Holder Account
+                authority_account.clone().key(),           // Edition Update Authority Account
+                master_metadata_account.clone().key(),     // Master Metadata Account
+                master_minter_account.clone().key(),       // Master Minter Account
+                edition_number,                            // Edition Number
             ),
             &[
-                metadata_account.clone(),  // Metadata Account
-                authority_account.clone(), // Authority Account
+                edition_metadata_account.clone(),    // Edition Metadata Account
+                edition_account.clone(),             // Edition Account
+                master_edition.clone(),              // Master Edition Account
+                edition_minter_account.clone(),      // Edition Minter Account
+                edition_marker_account.clone(),      // Edition Marker Account
+                authority_account.clone(),           // Authority Account
+                master_token_holder_account.clone(), // Master Token Holder Account
+                master_metadata_account.clone(),     // Master Metadata Account
             ],
+            signer_seeds,
         )?;
 
         Ok(())
     }
-}
-
-#[program]
-pub mod r21_metaplex_tests {
-    use super::*;
-    use crate::mpl_simplified_methods::*;
 
-    // region: Unit Test Only --- Initialize NFT
-
-    pub fn initialize_nft(ctx: Context<Initialize>) -> Result<()> {
-        let owner_account = ctx.accounts.owner_account.to_account_info();
-        let minter_account = ctx.accounts.minter_account.to_account_info();
+    pub fn _burn_nft<'info>(
+        metadata_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_holder_account: AccountInfo<'info>,
+        master_edition: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        collection_metadata: Option<AccountInfo<'info>>,
+    ) -> Result<()> {
+        _burn_nft_signed(
+            metadata_account,
+            owner_account,
+            minter_account,
+            token_holder_account,
+            master_edition,
+            token_program,
+            collection_metadata,
+            &[],
+        )
+    }
 
-        let token_program = ctx.accounts.token_program.to_account_info();
-        let rent_program = ctx.accounts.rent.to_account_info();
+    #[allow(clippy::too_many_arguments)]
+    pub fn _burn_nft_signed<'info>(
+        metadata_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_holder_account: AccountInfo<'info>,
+        master_edition: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        collection_metadata: Option<AccountInfo<'info>>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut account_infos = vec![
+            metadata_account.clone(),     // Metadata Account
+            owner_account.clone(),        // Owner Account
+            minter_account.clone(),       // Minter Account
+            token_holder_account.clone(), // Token Holder Account
+            master_edition.clone(),       // Master Edition Account
+            token_program.clone(),        // Token Program
+        ];
+        if let Some(collection_metadata) = &collection_metadata {
+            account_infos.push(collection_metadata.clone()); // Collection Metadata Account
+        }
+
+        invoke_signed(
+            &token_instruction::burn_nft(
+                TOKEN_METADATA_ID,                                // Target Program Address
+                metadata_account.clone().key(),                   // Metadata Account
+                owner_account.clone().key(),                      // Owner Account
+                minter_account.clone().key(),                     // Minter Account
+                token_holder_account.clone().key(),               // Token Holder Account
+                master_edition.clone().key(),                     // Master Edition Account
+                token_program.clone().key(),                      // Token Program
+                collection_metadata.map(|account| account.key()), // Collection Metadata Account
+            ),
+            &account_infos,
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn _burn_edition_nft<'info>(
+        metadata_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_holder_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        edition_marker_account: AccountInfo<'info>,
+        master_minter_account: AccountInfo<'info>,
+        master_token_holder_account: AccountInfo<'info>,
+        master_edition: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        _burn_edition_nft_signed(
+            metadata_account,
+            owner_account,
+            minter_account,
+            token_holder_account,
+            edition_account,
+            edition_marker_account,
+            master_minter_account,
+            master_token_holder_account,
+            master_edition,
+            token_program,
+            &[],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn _burn_edition_nft_signed<'info>(
+        metadata_account: AccountInfo<'info>,
+        owner_account: AccountInfo<'info>,
+        minter_account: AccountInfo<'info>,
+        token_holder_account: AccountInfo<'info>,
+        edition_account: AccountInfo<'info>,
+        edition_marker_account: AccountInfo<'info>,
+        master_minter_account: AccountInfo<'info>,
+        master_token_holder_account: AccountInfo<'info>,
+        master_edition: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        invoke_signed(
+            &token_instruction::burn_edition_nft(
+                TOKEN_METADATA_ID,                         // Target Program Address
+                metadata_account.clone().key(),            // Edition Metadata Account
+                owner_account.clone().key(),               // Owner Account
+                minter_account.clone().key(),              // Edition Minter Account
+                master_minter_account.clone().key(),       // Master Minter Account
+                token_holder_account.clone().key(),        // Edition Token Holder Account
+                master_token_holder_account.clone().key(), // Master Token Holder Account
+                master_edition.clone().key(),              // Master Edition Account
+                edition_account.clone().key(),             // Edition Account
+                edition_marker_account.clone().key(),      // Edition Marker Account
+                token_program.clone().key(),               // Token Program
+            ),
+            &[
+                metadata_account.clone(),            // Edition Metadata Account
+                owner_account.clone(),               // Owner Account
+                minter_account.clone(),              // Edition Minter Account
+                master_minter_account.clone(),       // Master Minter Account
+                token_holder_account.clone(),        // Edition Token Holder Account
+                master_token_holder_account.clone(), // Master Token Holder Account
+                master_edition.clone(),              // Master Edition Account
+                edition_account.clone(),             // Edition Account
+                edition_marker_account.clone(),      // Edition Marker Account
+                token_program.clone(),               // Token Program
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn _set_and_verify_collection<'info>(
+        metadata_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        collection_mint: AccountInfo<'info>,
+        collection_metadata: AccountInfo<'info>,
+        collection_master_edition: AccountInfo<'info>,
+    ) -> Result<()> {
+        _set_and_verify_collection_signed(
+            metadata_account,
+            authority_account.clone(),
+            authority_account,
+            collection_mint,
+            collection_metadata,
+            collection_master_edition,
+            &[],
+        )
+    }
+
+    pub fn _set_and_verify_collection_signed<'info>(
+        metadata_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        payer_account: AccountInfo<'info>,
+        collection_mint: AccountInfo<'info>,
+        collection_metadata: AccountInfo<'info>,
+        collection_master_edition: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        invoke_signed(
+            &token_instruction::set_and_verify_sized_collection_item(
+                TOKEN_METADATA_ID,                       // Target Program Address
+                metadata_account.clone().key(),          // Metadata Account
+                authority_account.clone().key(),         // Collection Update Authority Account
+                payer_account.clone().key(),             // Payer Account
+                authority_account.clone().key(),         // Update Authority Account
+                collection_mint.clone().key(),           // Collection Mint Account
+                collection_metadata.clone().key(),       // Collection Metadata Account
+                collection_master_edition.clone().key(), // Collection Master Edition Account
+                None,                                    // Collection Authority Record
+            ),
+            &[
+                metadata_account.clone(),          // Metadata Account
+                authority_account.clone(),         // Authority Account
+                payer_account.clone(),             // Payer Account
+                collection_mint.clone(),           // Collection Mint Account
+                collection_metadata.clone(),       // Collection Metadata Account
+                collection_master_edition.clone(), // Collection Master Edition Account
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn _update_metadata_account<'info>(
+        metadata_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        metadata_data: mpl_token_metadata::state::DataV2,
+    ) -> Result<()> {
+        _update_metadata_account_signed(metadata_account, authority_account, metadata_data, &[])
+    }
+
+    pub fn _update_metadata_account_signed<'info>(
+        metadata_account: AccountInfo<'info>,
+        authority_account: AccountInfo<'info>,
+        metadata_data: mpl_token_metadata::state::DataV2,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        require!(
+            metadata_data.seller_fee_basis_points <= 10000,
+            ErrorCode::InvalidSellerFeeBasisPoints
+        );
+
+        invoke_signed(
+            &token_instruction::update_metadata_accounts_v2(
+                TOKEN_METADATA_ID,               // Target Program Address
+                metadata_account.clone().key(),  // Metadata Account
+                authority_account.clone().key(), // Payer Account
+                None,                            // Update Authority Account
+                Some(metadata_data),             // Data
+                None,                            // Primary Sale
+                None,                            // Is Mutable
+            ),
+            &[
+                metadata_account.clone(),  // Metadata Account
+                authority_account.clone(), // Authority Account
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn _program_authority_bump(authority: &Pubkey) -> u8 {
+        Pubkey::find_program_address(&[PROGRAM_AUTHORITY_SEED, authority.as_ref()], &crate::ID).1
+    }
+
+    impl<'info> CreateNFT<'info> {
+        /// The account that becomes mint, freeze and update authority of the NFT.
+        pub fn nft_authority(&self, use_program_authority: bool) -> AccountInfo<'info> {
+            if use_program_authority {
+                self.program_authority.to_account_info()
+            } else {
+                self.authority_account.to_account_info()
+            }
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        pub fn create(
+            &self,
+            metadata_title: String,
+            metadata_symbol: String,
+            metadata_uri: String,
+            creators: Vec<NFTCreator>,
+            seller_fee_basis_points: u16,
+            max_supply: Option<u64>,
+            collection_details: Option<mpl_token_metadata::state::CollectionDetails>,
+            use_program_authority: bool,
+        ) -> Result<()> {
+            let minter = self.authority_account.key();
+            self.create_as(
+                minter,
+                _program_authority_bump(&minter),
+                &[],
+                metadata_title,
+                metadata_symbol,
+                metadata_uri,
+                creators,
+                seller_fee_basis_points,
+                max_supply,
+                collection_details,
+                use_program_authority,
+            )
+        }
+
+        /// Same as create, but the config minter check is done against `minter` instead of the signer.
+        /// Used by sales, where the buyer signs but the seller is the one allowed to mint, and by batches.
+        /// `program_authority_bump` is the bump of the program PDA of `minter`, callers take it from
+        /// `ctx.bumps` so it is not searched again. `mint_seeds` sign for a mint that is a PDA of this
+        /// program, an empty slice means the mint is a keypair that signed the transaction.
+        #[allow(clippy::too_many_arguments)]
+        pub fn create_as(
+            &self,
+            minter: Pubkey,
+            program_authority_bump: u8,
+            mint_seeds: &[&[u8]],
+            metadata_title: String,
+            metadata_symbol: String,
+            metadata_uri: String,
+            creators: Vec<NFTCreator>,
+            seller_fee_basis_points: u16,
+            max_supply: Option<u64>,
+            collection_details: Option<mpl_token_metadata::state::CollectionDetails>,
+            use_program_authority: bool,
+        ) -> Result<()> {
+            let payer_account = self.payer.to_account_info();
+            let recipient_account = self.recipient.to_account_info();
+            let nft_authority = self.nft_authority(use_program_authority);
+            let minter_account = self.minter_account.to_account_info();
+            let token_holder_account = self.token_holder_account.to_account_info();
+            let metadata_account = self.metadata_account.to_account_info();
+            let master_edition = self.master_edition_account.to_account_info();
+
+            let associated_token_program = self.associated_token_program.to_account_info();
+            let token_program = self.token_program.to_account_info();
+            let rent_program = self.rent.to_account_info();
+            let system_program = self.system_program.to_account_info();
+
+            /*
+             *  [NFT Authority]
+             *
+             *   - By default the signer is mint, freeze and update authority of the NFT.
+             *   - The token itself goes to the recipient wallet, which does not have to sign.
+             *   - With use_program_authority, the program PDA takes all three roles and signs for
+             *     them, so the program can act on the NFT later on.
+             *   - Every minter has their own program PDA, derived from their key, so nobody can sign
+             *     with the PDA of someone else. Sales derive it from the seller, not the buyer.
+             *   - Rent for every account is paid by the payer, which can be a relayer or the authority itself.
+             */
+
+            let bump = [program_authority_bump];
+            let program_authority_seeds: &[&[u8]] =
+                &[PROGRAM_AUTHORITY_SEED, minter.as_ref(), &bump];
+            let program_authority_signer = [program_authority_seeds];
+            let signer_seeds: &[&[&[u8]]] = if use_program_authority {
+                &program_authority_signer
+            } else {
+                &[]
+            };
+
+            /*
+             *  [Program Config]
+             *
+             *   - Without a config anyone can mint with whatever they pass in.
+             *   - With a config only the admin and the allowed minters can mint, unless creating is paused.
+             *   - An empty symbol falls back to the config symbol, empty creators fall back to the
+             *     config creators together with the config royalty.
+             *   - The config mint price is paid by the payer into the treasury before anything is created.
+             *   - When the config also sets a token price, that amount of the token is moved from the payment
+             *     token account into the treasury token account. Both are ignored otherwise.
+             */
+
+            let config = ProgramConfig::load(&self.config)?;
+            if let Some(config) = &config {
+                require!(
+                    config.paused & ProgramConfig::PAUSE_CREATE == 0,
+                    ErrorCode::InstructionPaused
+                );
+                require!(config.can_mint(&minter), ErrorCode::MinterNotAllowed);
+            }
+            let metadata_symbol = match &config {
+                Some(config) if metadata_symbol.is_empty() => config.symbol.clone(),
+                _ => metadata_symbol,
+            };
+            let (creators, seller_fee_basis_points) = match &config {
+                Some(config) if creators.is_empty() => {
+                    (config.creators.clone(), config.seller_fee_basis_points)
+                }
+                _ => (creators, seller_fee_basis_points),
+            };
+            let mint_fee = config.as_ref().map_or(0, |config| config.mint_price);
+            let token_fee = config
+                .as_ref()
+                .and_then(|config| {
+                    config
+                        .token_price_mint
+                        .map(|mint| (mint, config.token_price))
+                })
+                .filter(|(_, token_price)| *token_price > 0);
+
+            // Everything the caller supplied is checked before the first CPI
+            _validate_metadata_title(&metadata_title)?;
+            _validate_metadata_symbol(&metadata_symbol)?;
+            _validate_metadata_uri(&metadata_uri)?;
+            let creators = _build_creators(creators, nft_authority.key(), None)?;
+            let event_uri = metadata_uri.clone();
+
+            if mint_fee > 0 {
+                _transfer_lamports(
+                    system_program.clone(),
+                    payer_account.clone(),
+                    self.treasury.to_account_info(),
+                    mint_fee,
+                )?;
+                msg!("Mint Fee Paid!!!");
+            }
+
+            if let Some((token_price_mint, token_price)) = token_fee {
+                let payment_token_account =
+                    Account::<TokenAccount>::try_from(&self.payment_token_account)?;
+                let treasury_token_account =
+                    Account::<TokenAccount>::try_from(&self.treasury_token_account)?;
+                require_keys_eq!(
+                    payment_token_account.mint,
+                    token_price_mint,
+                    ErrorCode::InvalidTokenMint
+                );
+                require!(
+                    treasury_token_account.mint == token_price_mint
+                        && treasury_token_account.owner == self.treasury.key(),
+                    ErrorCode::InvalidTreasuryTokenAccount
+                );
+
+                token::transfer(
+                    CpiContext::new(
+                        token_program.clone(),
+                        Transfer {
+                            from: payment_token_account.to_account_info(),
+                            to: treasury_token_account.to_account_info(),
+                            authority: payer_account.clone(),
+                        },
+                    ),
+                    token_price,
+                )?;
+                msg!("Mint Token Fee Paid!!!");
+            }
+
+            let mint_signer = [mint_seeds];
+            let mint_signer_seeds: &[&[&[u8]]] = if mint_seeds.is_empty() {
+                &[]
+            } else {
+                &mint_signer
+            };
+            _create_account_signed(
+                system_program.clone(),
+                payer_account.clone(),
+                minter_account.clone(),
+                token_program.clone(),
+                mint_signer_seeds,
+            )?;
+            msg!("Mint Account Created!!!");
+
+            _initialize_mint_account(
+                nft_authority.clone(),
+                minter_account.clone(),
+                token_program.clone(),
+                rent_program.clone(),
+            )?;
+            msg!("Minter Initialized!!!");
+
+            // The token lands in the recipient's wallet, the authority only pays for it
+            _initialize_token_holder_account_signed(
+                recipient_account.clone(),
+                payer_account.clone(),
+                minter_account.clone(),
+                token_holder_account.clone(),
+                associated_token_program.clone(),
+                token_program.clone(),
+                rent_program.clone(),
+                system_program.clone(),
+                &[],
+            )?;
+            msg!("Associate Token Account Created!!!");
+
+            // Minting Token
+            _mint_token_to_account_signed(
+                minter_account.clone(),
+                token_holder_account.clone(),
+                nft_authority.clone(),
+                token_program.clone(),
+                signer_seeds,
+            )?;
+            msg!("Token Minted!!!");
+
+            _create_metadata_account_signed(
+                metadata_account.clone(),
+                minter_account.clone(),
+                nft_authority.clone(),
+                payer_account.clone(),
+                mpl_token_metadata::state::DataV2 {
+                    name: metadata_title,
+                    symbol: metadata_symbol,
+                    uri: metadata_uri,
+                    seller_fee_basis_points,
+                    creators: Some(creators),
+                    collection: None,
+                    uses: None,
+                },
+                collection_details,
+                signer_seeds,
+            )?;
+            msg!("Metadata Minted!!!");
+
+            // Creating Master Edition Metadata
+            _create_master_edition_account_signed(
+                master_edition.clone(),
+                minter_account.clone(),
+                token_holder_account.clone(),
+                metadata_account.clone(),
+                nft_authority.clone(),
+                payer_account.clone(),
+                rent_program.clone(),
+                max_supply,
+                signer_seeds,
+            )?;
+            msg!("Master Edition Minted!!!");
+
+            emit!(NFTCreated {
+                mint: minter_account.key(),
+                owner: recipient_account.key(),
+                update_authority: nft_authority.key(),
+                uri: event_uri,
+                max_supply,
+                mint_fee,
+                token_fee: token_fee.map_or(0, |(_, token_price)| token_price),
+                slot: Clock::get()?.slot,
+            });
+
+            Ok(())
+        }
+    }
+}
+
+#[program]
+pub mod r21_metaplex_tests {
+    use super::*;
+    use crate::mpl_simplified_methods::*;
+
+    // region: Unit Test Only --- Initialize NFT
+
+    pub fn initialize_nft(ctx: Context<Initialize>) -> Result<()> {
+        let owner_account = ctx.accounts.owner_account.to_account_info();
+        let minter_account = ctx.accounts.minter_account.to_account_info();
+
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let rent_program = ctx.accounts.rent.to_account_info();
         let system_program = ctx.accounts.system_program.to_account_info();
 
         /*
@@ -240,8 +1359,8 @@ pub mod r21_metaplex_tests {
          *    - The target program would be the token program.
          *    - We pass the pubkey of the user as the from pubkey.
          *    - We pass the pubkey of the mint (account) as the to pubkey. (The account address we want to create)
-         *    - The lamports is 1 SOL.
-         *    - The space is 82 bytes (idk where this value comes from honestly).
+         *    - The lamports is the rent exempt minimum for the mint, taken from the Rent sysvar.
+         *    - The space is the size of a SPL Token mint (Mint::LEN, 82 bytes).
          *    - The account owner is the token program.
          */
 
@@ -282,7 +1401,7 @@ pub mod r21_metaplex_tests {
          *
          *   - We create an associate token account.
          *     - The target program would be the associated token program.
-         *     - We pass the pubkey of the user as the payer pubkey.
+         *     - We pass the pubkey of the payer as the payer pubkey.
          *     - We pass the pubkey of the token account as the associated token pubkey.
          *     - We pass the pubkey of the user as the authority pubkey.
          *     - We pass the pubkey of the mint as the mint pubkey.
@@ -293,6 +1412,7 @@ pub mod r21_metaplex_tests {
 
         // Either uncomment this or uncomment the macro of this context
         let user_account = ctx.accounts.user_account.to_account_info();
+        let payer_account = ctx.accounts.payer.to_account_info();
         let mint_account = ctx.accounts.minter_account.to_account_info();
         let token_holder_account = ctx.accounts.token_holder_account.to_account_info();
 
@@ -301,14 +1421,16 @@ pub mod r21_metaplex_tests {
         let rent_program = ctx.accounts.rent.to_account_info();
         let system_program = ctx.accounts.system_program.to_account_info();
 
-        _initialize_token_holder_account(
+        _initialize_token_holder_account_signed(
             user_account.clone(),
+            payer_account.clone(),
             mint_account.clone(),
             token_holder_account.clone(),
             associated_token_program.clone(),
             token_program.clone(),
             rent_program.clone(),
             system_program.clone(),
+            &[],
         )?;
         msg!("Associate Token Account Created!!!");
 
@@ -321,7 +1443,10 @@ pub mod r21_metaplex_tests {
         metadata_symbol: String,
         metadata_uri: String,
     ) -> Result<()> {
+        ProgramConfig::require_not_paused(&ctx.accounts.config, ProgramConfig::PAUSE_MINT)?;
+
         let authority_account = ctx.accounts.authority_account.to_account_info();
+        let payer_account = ctx.accounts.payer.to_account_info();
         let minter_account = ctx.accounts.minter_account.to_account_info();
         let token_holder_account = ctx.accounts.token_holder_account.to_account_info();
         let metadata_account = ctx.accounts.metadata_account.to_account_info();
@@ -330,6 +1455,11 @@ pub mod r21_metaplex_tests {
         let token_program = ctx.accounts.token_program.to_account_info();
         let rent_program = ctx.accounts.rent.to_account_info();
 
+        _validate_metadata_title(&metadata_title)?;
+        _validate_metadata_symbol(&metadata_symbol)?;
+        _validate_metadata_uri(&metadata_uri)?;
+        let event_uri = metadata_uri.clone();
+
         // Minting Token
         _mint_token_to_account(
             minter_account.clone(),
@@ -339,31 +1469,68 @@ pub mod r21_metaplex_tests {
         )?;
         msg!("Token Minted!!!");
 
-        _create_metadata_account(
+        _create_metadata_account_signed(
             metadata_account.clone(),
             minter_account.clone(),
             authority_account.clone(),
-            metadata_title,
-            metadata_symbol,
-            metadata_uri,
+            payer_account.clone(),
+            mpl_token_metadata::state::DataV2 {
+                name: metadata_title,
+                symbol: metadata_symbol,
+                uri: metadata_uri,
+                seller_fee_basis_points: 1,
+                creators: Some(vec![
+                    mpl_token_metadata::state::Creator {
+                        address: minter_account.key(),
+                        verified: false,
+                        share: 100,
+                    },
+                    mpl_token_metadata::state::Creator {
+                        address: authority_account.key(),
+                        verified: false,
+                        share: 0,
+                    },
+                ]),
+                collection: None,
+                uses: None,
+            },
+            None,
+            &[],
         )?;
         msg!("Metadata Minted!!!");
 
         // Creating Master Edition Metadata
-        _create_master_edition_account(
+        _create_master_edition_account_signed(
             master_edition.clone(),
             minter_account.clone(),
             token_holder_account.clone(),
             metadata_account.clone(),
             authority_account.clone(),
+            payer_account.clone(),
             rent_program.clone(),
+            Some(1),
+            &[],
         )?;
         msg!("Master Edition Minted!!!");
 
+        // Same event as create_nft, mint_nft charges no fees
+        emit!(NFTCreated {
+            mint: minter_account.key(),
+            owner: ctx.accounts.token_holder_account.owner,
+            update_authority: authority_account.key(),
+            uri: event_uri,
+            max_supply: Some(1),
+            mint_fee: 0,
+            token_fee: 0,
+            slot: Clock::get()?.slot,
+        });
+
         Ok(())
     }
 
-    pub fn transfer_nft(ctx: Context<TransferNFT>) -> Result<()> {
+    pub fn transfer_nft(ctx: Context<TransferNFT>, close_sender: bool) -> Result<()> {
+        ProgramConfig::require_not_paused(&ctx.accounts.config, ProgramConfig::PAUSE_TRANSFER)?;
+
         let authority_account = ctx.accounts.authority.to_account_info();
         let sender_account = ctx.accounts.sender.to_account_info();
         let receiver_account = ctx.accounts.recipient.to_account_info();
@@ -384,200 +1551,2781 @@ pub mod r21_metaplex_tests {
         )?;
         msg!("Token Transferred!!!");
 
+        /*
+         *  [Close Sender]
+         *
+         *   - The sender account is empty after the transfer, closing it refunds its rent to the authority.
+         *   - Optional, since some wallets want to keep their token accounts around.
+         */
+
+        if close_sender {
+            token::close_account(CpiContext::new(
+                token_program.clone(),
+                CloseAccount {
+                    account: sender_account.clone(),
+                    destination: authority_account.clone(),
+                    authority: authority_account.clone(),
+                },
+            ))?;
+            msg!("Sender Token Account Closed!!!");
+        }
+
+        emit!(NFTTransferred {
+            mint: ctx.accounts.mint.key(),
+            from: authority_account.key(),
+            to: ctx.accounts.recipient_wallet.key(),
+            slot: Clock::get()?.slot,
+        });
+
         Ok(())
     }
 
     // endregion
 
-    // region: Official Functions
+    // region: Config
 
-    pub fn create_nft(
-        ctx: Context<CreateNFT>,
-        metadata_title: String,
-        metadata_symbol: String,
-        metadata_uri: String,
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        guardian: Pubkey,
+        allowed_minters: Vec<Pubkey>,
+        seller_fee_basis_points: u16,
+        creators: Vec<NFTCreator>,
+        symbol: String,
+        mint_price: u64,
     ) -> Result<()> {
-        let owner_account = ctx.accounts.authority_account.to_account_info();
-        let minter_account = ctx.accounts.minter_account.to_account_info();
-        let token_holder_account = ctx.accounts.token_holder_account.to_account_info();
-        let metadata_account = ctx.accounts.metadata_account.to_account_info();
-        let master_edition = ctx.accounts.master_edition_account.to_account_info();
+        let config = &mut ctx.accounts.config;
 
-        let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
-        let token_program = ctx.accounts.token_program.to_account_info();
-        let rent_program = ctx.accounts.rent.to_account_info();
-        let system_program = ctx.accounts.system_program.to_account_info();
+        // The config can only be created once, so only the upgrade authority of the program
+        // can create it, otherwise anyone could claim the admin right after a deploy
+        config.admin = ctx.accounts.admin.key();
+        config.guardian = guardian;
+        config.paused = 0;
+        config.allowed_minters = allowed_minters;
+        config.seller_fee_basis_points = seller_fee_basis_points;
+        config.creators = creators;
+        config.symbol = symbol;
+        config.mint_price = mint_price;
+        config.bump = *ctx.bumps.get("config").unwrap();
+        config.validate()?;
+        msg!("Config Initialized!!!");
 
-        _create_account(
-            system_program.clone(),
-            owner_account.clone(),
-            minter_account.clone(),
-            token_program.clone(),
+        // The treasury holds no data, it only has to stay rent exempt so small fees can land in it
+        _transfer_lamports(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            Rent::get()?.minimum_balance(0),
         )?;
-        msg!("Mint Account Created!!!");
+        msg!("Treasury Funded!!!");
 
-        _initialize_mint_account(
-            owner_account.clone(),
-            minter_account.clone(),
-            token_program.clone(),
-            rent_program.clone(),
-        )?;
-        msg!("Minter Initialized!!!");
+        Ok(())
+    }
 
-        _initialize_token_holder_account(
-            owner_account.clone(),
-            minter_account.clone(),
-            token_holder_account.clone(),
-            associated_token_program.clone(),
-            token_program.clone(),
-            rent_program.clone(),
-            system_program.clone(),
-        )?;
-        msg!("Associate Token Account Created!!!");
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        admin: Option<Pubkey>,
+        guardian: Option<Pubkey>,
+        allowed_minters: Option<Vec<Pubkey>>,
+        seller_fee_basis_points: Option<u16>,
+        creators: Option<Vec<NFTCreator>>,
+        symbol: Option<String>,
+        mint_price: Option<u64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
 
-        // Minting Token
-        _mint_token_to_account(
-            minter_account.clone(),
-            token_holder_account.clone(),
-            owner_account.clone(),
-            token_program.clone(),
-        )?;
-        msg!("Token Minted!!!");
+        // Same as update_nft_metadata, only what the caller supplied is overwritten
+        if let Some(admin) = admin {
+            config.admin = admin;
+        }
+        if let Some(guardian) = guardian {
+            config.guardian = guardian;
+        }
+        if let Some(allowed_minters) = allowed_minters {
+            config.allowed_minters = allowed_minters;
+        }
+        if let Some(seller_fee_basis_points) = seller_fee_basis_points {
+            config.seller_fee_basis_points = seller_fee_basis_points;
+        }
+        if let Some(creators) = creators {
+            config.creators = creators;
+        }
+        if let Some(symbol) = symbol {
+            config.symbol = symbol;
+        }
+        if let Some(mint_price) = mint_price {
+            config.mint_price = mint_price;
+        }
+        config.validate()?;
+        msg!("Config Updated!!!");
 
-        _create_metadata_account(
-            metadata_account.clone(),
-            minter_account.clone(),
-            owner_account.clone(),
-            metadata_title,
-            metadata_symbol,
-            metadata_uri,
-        )?;
-        msg!("Metadata Minted!!!");
+        Ok(())
+    }
 
-        // Creating Master Edition Metadata
-        _create_master_edition_account(
-            master_edition.clone(),
-            minter_account.clone(),
-            token_holder_account.clone(),
-            metadata_account.clone(),
-            owner_account.clone(),
-            rent_program.clone(),
-        )?;
-        msg!("Master Edition Minted!!!");
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_account = ctx.accounts.config.to_account_info();
 
-        msg!("NFT Created!!!");
+        /*
+         *  [Migrate Config]
+         *
+         *   - Configs created by older versions of the program are smaller and laid out differently,
+         *     they can't be loaded until they are migrated.
+         *   - The old layout is recognized by the account size. Fields it did not have yet get their
+         *     defaults: the admin is also the guardian, nothing is paused and there are no prices.
+         *   - The account grows to the current size, the admin pays the rent difference.
+         */
+
+        let config = ProgramConfig::from_legacy(&config_account.try_borrow_data()?)?;
+        require_keys_eq!(
+            config.admin,
+            ctx.accounts.admin.key(),
+            ErrorCode::InvalidAdmin
+        );
+
+        let rent_difference = Rent::get()?
+            .minimum_balance(ProgramConfig::LEN)
+            .saturating_sub(config_account.lamports());
+        if rent_difference > 0 {
+            _transfer_lamports(
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.admin.to_account_info(),
+                config_account.clone(),
+                rent_difference,
+            )?;
+        }
+
+        config_account.realloc(ProgramConfig::LEN, true)?;
+        config.try_serialize(&mut &mut config_account.try_borrow_mut_data()?[..])?;
+        msg!("Config Migrated!!!");
 
         Ok(())
     }
 
-    pub fn update_nft_metadata(
-        ctx: Context<UpdateNFTMetadata>,
-        metadata_title: String,
-        metadata_symbol: String,
-        metadata_uri: String,
-    ) -> Result<()> {
-        let owner_account = ctx.accounts.authority_account.to_account_info();
-        let metadata_account = ctx.accounts.metadata_account.to_account_info();
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, lamports: u64) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
 
-        _update_metadata_account(
-            metadata_account.clone(),
-            owner_account.clone(),
-            metadata_title,
-            metadata_symbol,
-            metadata_uri,
+        // The rent exempt minimum stays behind, otherwise the treasury would be garbage collected
+        let available = treasury
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(
+            lamports <= available,
+            ErrorCode::InsufficientTreasuryBalance
+        );
+
+        let bump = [*ctx.bumps.get("treasury").unwrap()];
+        let treasury_seeds: &[&[u8]] = &[TREASURY_SEED, &bump];
+
+        _transfer_lamports_signed(
+            ctx.accounts.system_program.to_account_info(),
+            treasury.clone(),
+            ctx.accounts.destination.to_account_info(),
+            lamports,
+            &[treasury_seeds],
         )?;
-        msg!("Metadata Updated!!!");
+        msg!("Treasury Withdrawn!!!");
 
         Ok(())
     }
 
-    // endregion
-}
+    pub fn set_token_price(
+        ctx: Context<UpdateConfig>,
+        token_price_mint: Option<Pubkey>,
+        token_price: u64,
+    ) -> Result<()> {
+        /*
+         *  [Token Price]
+         *
+         *   - Charges token_price of token_price_mint on every mint, on top of the lamport mint price.
+         *   - None turns it off again.
+         *   - The treasury token account is the associated token account of the treasury PDA,
+         *     it has to be created before the first mint.
+         */
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(mut)]
-    pub owner_account: Signer<'info>, // This is you
+        let config = &mut ctx.accounts.config;
 
-    #[account(mut)]
-    pub minter_account: Signer<'info>, // The mint account that will hold the token.
+        config.token_price_mint = token_price_mint;
+        config.token_price = token_price;
+        msg!("Token Price Set!!!");
 
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
-    pub system_program: Program<'info, System>,
+        Ok(())
+    }
+
+    pub fn withdraw_treasury_tokens(
+        ctx: Context<WithdrawTreasuryTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        let bump = [*ctx.bumps.get("treasury").unwrap()];
+        let treasury_seeds: &[&[u8]] = &[TREASURY_SEED, &bump];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.treasury_token_account.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                },
+                &[treasury_seeds],
+            ),
+            amount,
+        )?;
+        msg!("Treasury Tokens Withdrawn!!!");
+
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: u8) -> Result<()> {
+        /*
+         *  [Emergency Pause]
+         *
+         *   - The guardian can only add pause bits, the admin sets them as a whole and is the only
+         *     one who can resume. A leaked guardian key can halt the program but never undo a pause.
+         *   - The guardian does not protect against a compromised admin, the admin can resume
+         *     and replace the guardian through update_config.
+         *   - Every bit pauses one instruction, see the PAUSE_* flags on ProgramConfig.
+         *     u8::MAX pauses everything, 0 resumes everything.
+         */
+
+        let config = &mut ctx.accounts.config;
+        if ctx.accounts.authority.key() == config.admin {
+            config.paused = paused;
+        } else {
+            config.paused |= paused;
+        }
+        msg!("Paused Flags Set To {}!!!", config.paused);
+
+        Ok(())
+    }
+
+    // endregion
+
+    // region: Official Functions
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_nft(
+        ctx: Context<CreateNFT>,
+        metadata_title: String,
+        metadata_symbol: String,
+        metadata_uri: String,
+        creators: Vec<NFTCreator>,
+        seller_fee_basis_points: u16,
+        max_supply: Option<u64>,
+        use_program_authority: bool,
+    ) -> Result<()> {
+        ctx.accounts.create(
+            metadata_title,
+            metadata_symbol,
+            metadata_uri,
+            creators,
+            seller_fee_basis_points,
+            max_supply,
+            None,
+            use_program_authority,
+        )?;
+
+        msg!("NFT Created!!!");
+
+        Ok(())
+    }
+
+    pub fn create_collection_nft(
+        ctx: Context<CreateNFT>,
+        metadata_title: String,
+        metadata_symbol: String,
+        metadata_uri: String,
+        creators: Vec<NFTCreator>,
+        seller_fee_basis_points: u16,
+        use_program_authority: bool,
+    ) -> Result<()> {
+        /*
+         *  [Create Collection NFT]
+         *
+         *   - Same flow as create_nft, but the metadata is flagged as a sized collection parent.
+         *   - The master edition has a max supply of 0 so no prints can be made of the collection itself.
+         *   - The collection size starts at 0 and is incremented by Token Metadata
+         *     every time an item is verified against it.
+         */
+
+        ctx.accounts.create(
+            metadata_title,
+            metadata_symbol,
+            metadata_uri,
+            creators,
+            seller_fee_basis_points,
+            Some(0),
+            Some(mpl_token_metadata::state::CollectionDetails::V1 { size: 0 }),
+            use_program_authority,
+        )?;
+
+        msg!("Collection NFT Created!!!");
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_nft_in_collection(
+        ctx: Context<CreateNFTInCollection>,
+        metadata_title: String,
+        metadata_symbol: String,
+        metadata_uri: String,
+        creators: Vec<NFTCreator>,
+        seller_fee_basis_points: u16,
+        max_supply: Option<u64>,
+        use_program_authority: bool,
+    ) -> Result<()> {
+        let payer_account = ctx.accounts.nft.payer.to_account_info();
+        let nft_authority = ctx.accounts.nft.nft_authority(use_program_authority);
+        let metadata_account = ctx.accounts.nft.metadata_account.to_account_info();
+        let collection_mint = ctx.accounts.collection_mint.to_account_info();
+        let collection_metadata = ctx.accounts.collection_metadata.to_account_info();
+        let collection_master_edition = ctx.accounts.collection_master_edition.to_account_info();
+
+        // The collection has to be managed by the same authority the new NFT gets
+        require_keys_eq!(
+            ctx.accounts.collection_metadata.update_authority,
+            nft_authority.key(),
+            ErrorCode::InvalidUpdateAuthority
+        );
+
+        ctx.accounts.nft.create(
+            metadata_title,
+            metadata_symbol,
+            metadata_uri,
+            creators,
+            seller_fee_basis_points,
+            max_supply,
+            None,
+            use_program_authority,
+        )?;
+
+        /*
+         *  [Set And Verify Collection]
+         *
+         *   - Attaches the new NFT to the collection and verifies it in one CPI.
+         *   - The NFT authority has to be the update authority of both the NFT and the collection.
+         */
+
+        let authority_key = ctx.accounts.nft.authority_account.key();
+        let bump = [_program_authority_bump(&authority_key)];
+        let program_authority_seeds: &[&[u8]] =
+            &[PROGRAM_AUTHORITY_SEED, authority_key.as_ref(), &bump];
+        let program_authority_signer = [program_authority_seeds];
+        let signer_seeds: &[&[&[u8]]] = if use_program_authority {
+            &program_authority_signer
+        } else {
+            &[]
+        };
+
+        _set_and_verify_collection_signed(
+            metadata_account.clone(),
+            nft_authority.clone(),
+            payer_account.clone(),
+            collection_mint.clone(),
+            collection_metadata.clone(),
+            collection_master_edition.clone(),
+            signer_seeds,
+        )?;
+        msg!("Collection Verified!!!");
+
+        msg!("NFT Created!!!");
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_nft_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateNFTBatch<'info>>,
+        batch_id: u64,
+        nfts: Vec<BatchNFTMetadata>,
+        creators: Vec<NFTCreator>,
+        seller_fee_basis_points: u16,
+        max_supply: Option<u64>,
+        use_program_authority: bool,
+    ) -> Result<()> {
+        /*
+         *  [Create NFT Batch]
+         *
+         *   - Every NFT passes 4 remaining accounts, in order:
+         *     mint, token holder, metadata and master edition.
+         *   - The mints are PDAs of this program, seeded with the authority, the batch id and the
+         *     index of the NFT, so the program signs for them and the batch needs no extra signatures.
+         *     The client derives them and passes each bump along, the bump is only checked here.
+         *   - All NFTs share the authority, payer, recipient, creators and royalty, only the
+         *     title, symbol and uri differ. Each one goes through the same create path as create_nft,
+         *     so the config, fees and events apply to every single NFT.
+         *   - A legacy transaction only fits 2 or 3 NFTs, depending on the metadata. Larger batches
+         *     have to be sent as a v0 transaction with the shared accounts and the batch accounts in an
+         *     address lookup table, up to MAX_BATCH_SIZE. One NFT takes 6 CPIs, so the compute unit
+         *     limit has to be raised as well.
+         */
+
+        require!(
+            !nfts.is_empty() && nfts.len() <= MAX_BATCH_SIZE,
+            ErrorCode::InvalidBatchSize
+        );
+        require!(
+            ctx.remaining_accounts.len() == nfts.len() * 4,
+            ErrorCode::MissingBatchAccounts
+        );
+
+        let count = nfts.len() as u8;
+        let authority_key = ctx.accounts.authority_account.key();
+        let batch_id = batch_id.to_le_bytes();
+        let program_authority_bump = *ctx.bumps.get("program_authority").unwrap();
+
+        for (index, (nft, accounts)) in nfts
+            .into_iter()
+            .zip(ctx.remaining_accounts.chunks(4))
+            .enumerate()
+        {
+            let index = [index as u8];
+            let mint_bump = [nft.mint_bump];
+            let mint_seeds: &[&[u8]] = &[
+                BATCH_MINT_SEED,
+                authority_key.as_ref(),
+                &batch_id,
+                &index,
+                &mint_bump,
+            ];
+
+            // Token Metadata checks the metadata and master edition derivations itself
+            let minter_account = &accounts[0];
+            let mint_key = Pubkey::create_program_address(mint_seeds, ctx.program_id)
+                .map_err(|_| ErrorCode::InvalidBatchAccounts)?;
+            require_keys_eq!(
+                minter_account.key(),
+                mint_key,
+                ErrorCode::InvalidBatchAccounts
+            );
+
+            CreateNFT {
+                authority_account: ctx.accounts.authority_account.clone(),
+                payer: ctx.accounts.payer.clone(),
+                minter_account: UncheckedAccount::try_from(minter_account.clone()),
+                recipient: ctx.accounts.recipient.clone(),
+                token_holder_account: UncheckedAccount::try_from(accounts[1].clone()),
+                metadata_account: UncheckedAccount::try_from(accounts[2].clone()),
+                master_edition_account: UncheckedAccount::try_from(accounts[3].clone()),
+                program_authority: ctx.accounts.program_authority.clone(),
+                config: ctx.accounts.config.clone(),
+                treasury: ctx.accounts.treasury.clone(),
+                payment_token_account: ctx.accounts.payment_token_account.clone(),
+                treasury_token_account: ctx.accounts.treasury_token_account.clone(),
+                token_program: ctx.accounts.token_program.clone(),
+                associated_token_program: ctx.accounts.associated_token_program.clone(),
+                rent: ctx.accounts.rent.clone(),
+                system_program: ctx.accounts.system_program.clone(),
+                token_metadata_program: ctx.accounts.token_metadata_program.clone(),
+            }
+            .create_as(
+                authority_key,
+                program_authority_bump,
+                mint_seeds,
+                nft.title,
+                nft.symbol,
+                nft.uri,
+                creators.clone(),
+                seller_fee_basis_points,
+                max_supply,
+                None,
+                use_program_authority,
+            )?;
+        }
+        msg!("{} NFTs Created!!!", count);
+
+        emit!(NFTBatchCreated {
+            authority: ctx.accounts.authority_account.key(),
+            count,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn withdraw_program_authority(ctx: Context<WithdrawProgramAuthority>) -> Result<()> {
+        let program_authority = ctx.accounts.program_authority.to_account_info();
+
+        // Royalties of NFTs where the program PDA is a creator pile up in the PDA,
+        // only the authority it is derived from can take them out. It holds no data,
+        // so it is emptied completely instead of keeping a rent exempt minimum.
+        let authority_key = ctx.accounts.authority.key();
+        let bump = [*ctx.bumps.get("program_authority").unwrap()];
+        let program_authority_seeds: &[&[u8]] =
+            &[PROGRAM_AUTHORITY_SEED, authority_key.as_ref(), &bump];
+
+        _transfer_lamports_signed(
+            ctx.accounts.system_program.to_account_info(),
+            program_authority.clone(),
+            ctx.accounts.authority.to_account_info(),
+            program_authority.lamports(),
+            &[program_authority_seeds],
+        )?;
+        msg!("Program Authority Withdrawn!!!");
+
+        Ok(())
+    }
+
+    pub fn print_edition(ctx: Context<PrintEdition>, edition_number: u64) -> Result<()> {
+        let owner_account = ctx.accounts.authority_account.to_account_info();
+        let minter_account = ctx.accounts.minter_account.to_account_info();
+        let token_holder_account = ctx.accounts.token_holder_account.to_account_info();
+        let metadata_account = ctx.accounts.metadata_account.to_account_info();
+        let edition_account = ctx.accounts.edition_account.to_account_info();
+        let edition_marker_account = ctx.accounts.edition_marker_account.to_account_info();
+        let master_minter_account = ctx.accounts.master_minter_account.to_account_info();
+        let master_token_holder_account =
+            ctx.accounts.master_token_holder_account.to_account_info();
+        let master_metadata_account = ctx.accounts.master_metadata_account.to_account_info();
+        let master_edition = ctx.accounts.master_edition_account.to_account_info();
+
+        let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let rent_program = ctx.accounts.rent.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        /*
+         *  [Print Edition]
+         *
+         *   - Token Metadata expects the edition mint to already exist and hold exactly 1 token,
+         *     so we go through the same mint steps as create_nft first.
+         *   - The edition number has to be unused and within the max supply of the master edition.
+         *   - The edition marker account is the PDA that tracks which numbers were printed,
+         *     one marker covers 248 editions.
+         */
+
+        _create_account(
+            system_program.clone(),
+            owner_account.clone(),
+            minter_account.clone(),
+            token_program.clone(),
+        )?;
+        msg!("Mint Account Created!!!");
+
+        _initialize_mint_account(
+            owner_account.clone(),
+            minter_account.clone(),
+            token_program.clone(),
+            rent_program.clone(),
+        )?;
+        msg!("Minter Initialized!!!");
+
+        _initialize_token_holder_account(
+            owner_account.clone(),
+            minter_account.clone(),
+            token_holder_account.clone(),
+            associated_token_program.clone(),
+            token_program.clone(),
+            rent_program.clone(),
+            system_program.clone(),
+        )?;
+        msg!("Associate Token Account Created!!!");
+
+        // Minting Token
+        _mint_token_to_account(
+            minter_account.clone(),
+            token_holder_account.clone(),
+            owner_account.clone(),
+            token_program.clone(),
+        )?;
+        msg!("Token Minted!!!");
+
+        _mint_new_edition_from_master_edition(
+            metadata_account.clone(),
+            edition_account.clone(),
+            minter_account.clone(),
+            edition_marker_account.clone(),
+            master_edition.clone(),
+            master_minter_account.clone(),
+            master_metadata_account.clone(),
+            master_token_holder_account.clone(),
+            owner_account.clone(),
+            edition_number,
+        )?;
+        msg!("Edition {} Printed!!!", edition_number);
+
+        emit!(NFTPrinted {
+            mint: minter_account.key(),
+            master_mint: master_minter_account.key(),
+            owner: owner_account.key(),
+            uri: ctx
+                .accounts
+                .master_metadata_account
+                .data
+                .uri
+                .trim_matches(char::from(0))
+                .to_string(),
+            edition_number,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn burn_nft<'info>(ctx: Context<'_, '_, '_, 'info, BurnNFT<'info>>) -> Result<()> {
+        let owner_account = ctx.accounts.authority_account.to_account_info();
+        let minter_account = ctx.accounts.minter_account.to_account_info();
+        let token_holder_account = ctx.accounts.token_holder_account.to_account_info();
+        let metadata_account = ctx.accounts.metadata_account.to_account_info();
+        let edition_account = ctx.accounts.edition_account.to_account_info();
+
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        /*
+         *  [Burn NFT]
+         *
+         *   - Token Metadata burns the token, closes the token account, metadata and edition
+         *     accounts and sends their rent back to the holder.
+         *   - The edition account tells us which kind of NFT this is:
+         *     - Master editions can pass the collection metadata as the only remaining account,
+         *       it is required if the NFT is a verified member of a collection.
+         *     - Print editions have to pass these remaining accounts, in order:
+         *       master minter, master token holder, master edition and edition marker.
+         */
+
+        let edition_key = edition_account
+            .try_borrow_data()?
+            .first()
+            .copied()
+            .ok_or(ErrorCode::InvalidEditionAccount)?;
+
+        // Read before burning, Token Metadata closes both accounts
+        let uri = ctx
+            .accounts
+            .metadata_account
+            .data
+            .uri
+            .trim_matches(char::from(0))
+            .to_string();
+
+        let edition_number = if edition_key == mpl_token_metadata::state::Key::MasterEditionV2 as u8
+        {
+            _burn_nft(
+                metadata_account.clone(),
+                owner_account.clone(),
+                minter_account.clone(),
+                token_holder_account.clone(),
+                edition_account.clone(),
+                token_program.clone(),
+                ctx.remaining_accounts.first().cloned(),
+            )?;
+            None
+        } else if edition_key == mpl_token_metadata::state::Key::EditionV1 as u8 {
+            require!(
+                ctx.remaining_accounts.len() == 4,
+                ErrorCode::MissingEditionAccounts
+            );
+            let master_minter_account = ctx.remaining_accounts[0].clone();
+            let master_token_holder_account = ctx.remaining_accounts[1].clone();
+            let master_edition = ctx.remaining_accounts[2].clone();
+            let edition_marker_account = ctx.remaining_accounts[3].clone();
+            let edition_number =
+                mpl_token_metadata::state::Edition::from_account_info(&edition_account)?.edition;
+
+            _burn_edition_nft(
+                metadata_account.clone(),
+                owner_account.clone(),
+                minter_account.clone(),
+                token_holder_account.clone(),
+                edition_account.clone(),
+                edition_marker_account.clone(),
+                master_minter_account.clone(),
+                master_token_holder_account.clone(),
+                master_edition.clone(),
+                token_program.clone(),
+            )?;
+            Some(edition_number)
+        } else {
+            return err!(ErrorCode::InvalidEditionAccount);
+        };
+        msg!("NFT Burned!!!");
+
+        emit!(NFTBurned {
+            mint: minter_account.key(),
+            owner: owner_account.key(),
+            uri,
+            edition_number,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_nft_metadata(
+        ctx: Context<UpdateNFTMetadata>,
+        metadata_title: Option<String>,
+        metadata_symbol: Option<String>,
+        metadata_uri: Option<String>,
+        seller_fee_basis_points: Option<u16>,
+        creators: Option<Vec<NFTCreator>>,
+    ) -> Result<()> {
+        ProgramConfig::require_not_paused(&ctx.accounts.config, ProgramConfig::PAUSE_UPDATE)?;
+
+        let metadata_account = ctx.accounts.metadata_account.to_account_info();
+
+        /*
+         *  [Update Metadata Account]
+         *
+         *   - Token Metadata replaces the whole data struct on update, so we start from what is
+         *     currently stored and only overwrite the fields the caller supplied.
+         *   - Strings are stored padded with null bytes, those are trimmed before being sent back.
+         *   - Collection and uses are always carried over as they are.
+         *   - NFTs created with use_program_authority are updated by the program PDA of the signer,
+         *     the program signs for it.
+         */
+
+        let authority_key = ctx.accounts.authority_account.key();
+        let bump = [*ctx.bumps.get("program_authority").unwrap()];
+        let program_authority_seeds: &[&[u8]] =
+            &[PROGRAM_AUTHORITY_SEED, authority_key.as_ref(), &bump];
+        let program_authority_signer = [program_authority_seeds];
+        let use_program_authority =
+            ctx.accounts.metadata_account.update_authority == ctx.accounts.program_authority.key();
+        let (owner_account, signer_seeds): (AccountInfo, &[&[&[u8]]]) = if use_program_authority {
+            (
+                ctx.accounts.program_authority.to_account_info(),
+                &program_authority_signer,
+            )
+        } else {
+            (ctx.accounts.authority_account.to_account_info(), &[])
+        };
+
+        if let Some(metadata_title) = &metadata_title {
+            _validate_metadata_title(metadata_title)?;
+        }
+        if let Some(metadata_symbol) = &metadata_symbol {
+            _validate_metadata_symbol(metadata_symbol)?;
+        }
+        if let Some(metadata_uri) = &metadata_uri {
+            _validate_metadata_uri(metadata_uri)?;
+        }
+
+        let existing = &ctx.accounts.metadata_account;
+
+        let creators = match creators {
+            Some(creators) => Some(_build_creators(
+                creators,
+                owner_account.key(),
+                existing.data.creators.as_deref(),
+            )?),
+            None => existing.data.creators.clone(),
+        };
+
+        let metadata_data = mpl_token_metadata::state::DataV2 {
+            name: metadata_title
+                .unwrap_or_else(|| existing.data.name.trim_matches(char::from(0)).to_string()),
+            symbol: metadata_symbol
+                .unwrap_or_else(|| existing.data.symbol.trim_matches(char::from(0)).to_string()),
+            uri: metadata_uri
+                .unwrap_or_else(|| existing.data.uri.trim_matches(char::from(0)).to_string()),
+            seller_fee_basis_points: seller_fee_basis_points
+                .unwrap_or(existing.data.seller_fee_basis_points),
+            creators,
+            collection: existing.collection.clone(),
+            uses: existing.uses.clone(),
+        };
+        let event_uri = metadata_data.uri.clone();
+
+        _update_metadata_account_signed(
+            metadata_account.clone(),
+            owner_account.clone(),
+            metadata_data,
+            signer_seeds,
+        )?;
+        msg!("Metadata Updated!!!");
+
+        emit!(NFTMetadataUpdated {
+            mint: ctx.accounts.minter_account.key(),
+            update_authority: owner_account.key(),
+            uri: event_uri,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    // endregion
+
+    // region: Marketplace
+
+    pub fn list_nft(ctx: Context<ListNFT>, price: u64) -> Result<()> {
+        /*
+         *  [List NFT]
+         *
+         *   - The NFT moves from the seller into an escrow token account owned by the listing PDA,
+         *     so the seller can't sell or move it twice while it is listed.
+         *   - The listing PDA records the seller and the price in lamports.
+         */
+
+        require!(price > 0, ErrorCode::InvalidPrice);
+
+        let listing = &mut ctx.accounts.listing;
+        listing.seller = ctx.accounts.seller.key();
+        listing.mint = ctx.accounts.mint.key();
+        listing.price = price;
+        listing.mode = ListingMode::Escrow;
+        listing.bump = *ctx.bumps.get("listing").unwrap();
+
+        _transfer_token(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            1,
+        )?;
+        msg!("NFT Listed!!!");
+
+        emit!(NFTListed {
+            mint: listing.mint,
+            seller: listing.seller,
+            price,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn buy_nft<'info>(ctx: Context<'_, '_, '_, 'info, BuyNFT<'info>>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        /*
+         *  [Buy NFT]
+         *
+         *   - The buyer pays the royalty to the creators first, following seller_fee_basis_points and
+         *     the shares of the metadata. Every creator has to be passed as a remaining account, in order.
+         *   - The rest of the listing price goes straight to the seller.
+         *   - The listing PDA signs the NFT out of escrow into the buyer's token account,
+         *     which is created when needed.
+         *   - Escrow and listing are closed, their rent goes back to the seller.
+         */
+
+        let (splits, seller_proceeds) = _pay_royalties(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.metadata_account,
+            ctx.remaining_accounts,
+            listing.price,
+            &[],
+        )?;
+        msg!("Royalties Paid!!!");
+
+        _transfer_lamports(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            seller_proceeds,
+        )?;
+        msg!("Seller Paid!!!");
+
+        emit!(RoyaltiesPaid {
+            mint: listing.mint,
+            price: listing.price,
+            seller_proceeds,
+            splits,
+            slot: Clock::get()?.slot,
+        });
+
+        let mint = listing.mint;
+        let bump = [listing.bump];
+        let listing_seeds: &[&[u8]] = &[LISTING_SEED, mint.as_ref(), &bump];
+
+        _transfer_token_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.buyer_token_account.to_account_info(),
+            listing.to_account_info(),
+            1,
+            &[listing_seeds],
+        )?;
+        msg!("Token Transferred!!!");
+
+        _close_token_account_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            listing.to_account_info(),
+            &[listing_seeds],
+        )?;
+        msg!("Escrow Closed!!!");
+
+        emit!(NFTSold {
+            mint,
+            seller: listing.seller,
+            buyer: ctx.accounts.buyer.key(),
+            price: listing.price,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        let mint = listing.mint;
+        let bump = [listing.bump];
+        let listing_seeds: &[&[u8]] = &[LISTING_SEED, mint.as_ref(), &bump];
+
+        // Same as buy_nft, except the NFT goes back to the seller and nobody pays
+        _transfer_token_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            listing.to_account_info(),
+            1,
+            &[listing_seeds],
+        )?;
+        msg!("Token Returned!!!");
+
+        _close_token_account_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            listing.to_account_info(),
+            &[listing_seeds],
+        )?;
+        msg!("Escrow Closed!!!");
+
+        emit!(ListingCancelled {
+            mint,
+            seller: listing.seller,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn list_nft_delegated(ctx: Context<ListNFTDelegated>, price: u64) -> Result<()> {
+        /*
+         *  [List NFT Delegated]
+         *
+         *   - The NFT stays in the seller's wallet, the listing PDA only becomes its delegate.
+         *   - The listing PDA then freezes the token account through Token Metadata, so the seller
+         *     can't move it or revoke the delegate while it is listed.
+         */
+
+        require!(price > 0, ErrorCode::InvalidPrice);
+
+        let listing = &mut ctx.accounts.listing;
+        listing.seller = ctx.accounts.seller.key();
+        listing.mint = ctx.accounts.mint.key();
+        listing.price = price;
+        listing.mode = ListingMode::Delegated;
+        listing.bump = *ctx.bumps.get("listing").unwrap();
+
+        _approve_token_delegate(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            listing.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            1,
+        )?;
+        msg!("Delegate Approved!!!");
+
+        let mint = listing.mint;
+        let bump = [listing.bump];
+        let listing_seeds: &[&[u8]] = &[LISTING_SEED, mint.as_ref(), &bump];
+
+        _freeze_delegated_account_signed(
+            listing.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.edition_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            &[listing_seeds],
+        )?;
+        msg!("NFT Listed!!!");
+
+        emit!(NFTListed {
+            mint,
+            seller: listing.seller,
+            price,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn buy_nft_delegated<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyNFTDelegated<'info>>,
+    ) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        // Payment is the same as buy_nft, only the NFT comes out of the seller's wallet instead of escrow
+        let (splits, seller_proceeds) = _pay_royalties(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.metadata_account,
+            ctx.remaining_accounts,
+            listing.price,
+            &[],
+        )?;
+        msg!("Royalties Paid!!!");
+
+        _transfer_lamports(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            seller_proceeds,
+        )?;
+        msg!("Seller Paid!!!");
+
+        emit!(RoyaltiesPaid {
+            mint: listing.mint,
+            price: listing.price,
+            seller_proceeds,
+            splits,
+            slot: Clock::get()?.slot,
+        });
+
+        let mint = listing.mint;
+        let bump = [listing.bump];
+        let listing_seeds: &[&[u8]] = &[LISTING_SEED, mint.as_ref(), &bump];
+
+        _thaw_delegated_account_signed(
+            listing.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.edition_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            token_program.clone(),
+            &[listing_seeds],
+        )?;
+        msg!("Token Thawed!!!");
+
+        // Moving the whole delegated amount clears the delegate as well
+        _transfer_token_signed(
+            token_program.clone(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.buyer_token_account.to_account_info(),
+            listing.to_account_info(),
+            1,
+            &[listing_seeds],
+        )?;
+        msg!("Token Transferred!!!");
+
+        emit!(NFTSold {
+            mint,
+            seller: listing.seller,
+            buyer: ctx.accounts.buyer.key(),
+            price: listing.price,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_delegated_listing(ctx: Context<CancelDelegatedListing>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        let mint = listing.mint;
+        let bump = [listing.bump];
+        let listing_seeds: &[&[u8]] = &[LISTING_SEED, mint.as_ref(), &bump];
+
+        _thaw_delegated_account_signed(
+            listing.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.edition_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            token_program.clone(),
+            &[listing_seeds],
+        )?;
+        msg!("Token Thawed!!!");
+
+        _revoke_token_delegate(
+            token_program.clone(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+        )?;
+        msg!("Delegate Revoked!!!");
+
+        emit!(ListingCancelled {
+            mint,
+            seller: listing.seller,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    // endregion
+
+    // region: Auctions
+
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        reserve_price: u64,
+        min_increment: u64,
+        duration: i64,
+        extension_window: i64,
+    ) -> Result<()> {
+        /*
+         *  [Create Auction]
+         *
+         *   - The NFT moves into an escrow token account owned by the auction PDA, same as list_nft.
+         *   - Bids are held in the bid vault PDA, it only ever holds the highest bid.
+         *   - The vault is funded with its rent exempt minimum so bids of any size can land in it,
+         *     the seller gets it back once the auction is settled or cancelled.
+         *   - duration and extension_window are in seconds.
+         */
+
+        require!(
+            min_increment > 0 && duration > 0 && extension_window >= 0,
+            ErrorCode::InvalidAuctionParams
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let auction = &mut ctx.accounts.auction;
+        auction.seller = ctx.accounts.seller.key();
+        auction.mint = ctx.accounts.mint.key();
+        auction.reserve_price = reserve_price;
+        auction.min_increment = min_increment;
+        auction.end_time = now
+            .checked_add(duration)
+            .ok_or(ErrorCode::InvalidAuctionParams)?;
+        auction.extension_window = extension_window;
+        auction.highest_bidder = Pubkey::default();
+        auction.highest_bid = 0;
+        auction.bump = *ctx.bumps.get("auction").unwrap();
+        auction.vault_bump = *ctx.bumps.get("bid_vault").unwrap();
+
+        _transfer_token(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            1,
+        )?;
+        msg!("Token Escrowed!!!");
+
+        _transfer_lamports(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            ctx.accounts.bid_vault.to_account_info(),
+            Rent::get()?.minimum_balance(0),
+        )?;
+        msg!("Auction Created!!!");
+
+        emit!(AuctionCreated {
+            mint: auction.mint,
+            seller: auction.seller,
+            reserve_price,
+            end_time: auction.end_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let auction = &mut ctx.accounts.auction;
+
+        /*
+         *  [Place Bid]
+         *
+         *   - The first bid has to reach the reserve price, every other bid has to beat the highest
+         *     bid by at least the minimum increment.
+         *   - The new bid goes into the vault and the outbid bidder is refunded from it right away.
+         *   - A bid within the extension window of the end pushes the end back, so nobody can snipe.
+         */
+
+        require!(now < auction.end_time, ErrorCode::AuctionEnded);
+        if auction.highest_bid == 0 {
+            require!(
+                amount >= auction.reserve_price && amount > 0,
+                ErrorCode::BidTooLow
+            );
+        } else {
+            require!(
+                amount >= auction.highest_bid.saturating_add(auction.min_increment),
+                ErrorCode::BidTooLow
+            );
+        }
+
+        _transfer_lamports(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.bidder.to_account_info(),
+            ctx.accounts.bid_vault.to_account_info(),
+            amount,
+        )?;
+        msg!("Bid Placed!!!");
+
+        if auction.highest_bid > 0 {
+            let mint = auction.mint;
+            let vault_bump = [auction.vault_bump];
+            let vault_seeds: &[&[u8]] = &[BID_VAULT_SEED, mint.as_ref(), &vault_bump];
+
+            _transfer_lamports_signed(
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.bid_vault.to_account_info(),
+                ctx.accounts.previous_bidder.to_account_info(),
+                auction.highest_bid,
+                &[vault_seeds],
+            )?;
+            msg!("Previous Bidder Refunded!!!");
+        }
+
+        auction.highest_bidder = ctx.accounts.bidder.key();
+        auction.highest_bid = amount;
+        if auction.end_time - now < auction.extension_window {
+            auction.end_time = now
+                .checked_add(auction.extension_window)
+                .ok_or(ErrorCode::InvalidAuctionParams)?;
+            msg!("Auction Extended!!!");
+        }
+
+        emit!(BidPlaced {
+            mint: auction.mint,
+            bidder: auction.highest_bidder,
+            amount,
+            end_time: auction.end_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleAuction<'info>>,
+    ) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let bid_vault = ctx.accounts.bid_vault.to_account_info();
+
+        /*
+         *  [Settle Auction]
+         *
+         *   - Anyone can settle once the end time has passed.
+         *   - With a winning bid the vault pays the creator royalties and the seller, the same way
+         *     buy_nft does, and the NFT goes to the winner. Creators are passed as remaining accounts.
+         *   - Without bids the NFT simply goes back to the seller.
+         *   - Escrow, vault and auction are closed, their rent goes back to the seller.
+         */
+
+        require!(
+            Clock::get()?.unix_timestamp >= auction.end_time,
+            ErrorCode::AuctionNotEnded
+        );
+
+        let mint = auction.mint;
+        let bump = [auction.bump];
+        let auction_seeds: &[&[u8]] = &[AUCTION_SEED, mint.as_ref(), &bump];
+        let vault_bump = [auction.vault_bump];
+        let vault_seeds: &[&[u8]] = &[BID_VAULT_SEED, mint.as_ref(), &vault_bump];
+
+        if auction.highest_bid > 0 {
+            let (splits, seller_proceeds) = _pay_royalties(
+                system_program.clone(),
+                bid_vault.clone(),
+                &ctx.accounts.metadata_account,
+                ctx.remaining_accounts,
+                auction.highest_bid,
+                &[vault_seeds],
+            )?;
+            msg!("Royalties Paid!!!");
+
+            _transfer_lamports_signed(
+                system_program.clone(),
+                bid_vault.clone(),
+                ctx.accounts.seller.to_account_info(),
+                seller_proceeds,
+                &[vault_seeds],
+            )?;
+            msg!("Seller Paid!!!");
+
+            emit!(RoyaltiesPaid {
+                mint,
+                price: auction.highest_bid,
+                seller_proceeds,
+                splits,
+                slot: Clock::get()?.slot,
+            });
+        }
+
+        _transfer_token_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.winner_token_account.to_account_info(),
+            auction.to_account_info(),
+            1,
+            &[auction_seeds],
+        )?;
+        msg!("Token Transferred!!!");
+
+        _close_token_account_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            auction.to_account_info(),
+            &[auction_seeds],
+        )?;
+
+        // Whatever is left is the rent exempt minimum the seller put in
+        _transfer_lamports_signed(
+            system_program.clone(),
+            bid_vault.clone(),
+            ctx.accounts.seller.to_account_info(),
+            bid_vault.lamports(),
+            &[vault_seeds],
+        )?;
+        msg!("Auction Settled!!!");
+
+        emit!(AuctionSettled {
+            mint,
+            seller: auction.seller,
+            winner: ctx.accounts.winner.key(),
+            price: auction.highest_bid,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let bid_vault = ctx.accounts.bid_vault.to_account_info();
+
+        // Once someone bid the seller has to go through settle_auction
+        require!(auction.highest_bid == 0, ErrorCode::AuctionHasBids);
+
+        let mint = auction.mint;
+        let bump = [auction.bump];
+        let auction_seeds: &[&[u8]] = &[AUCTION_SEED, mint.as_ref(), &bump];
+        let vault_bump = [auction.vault_bump];
+        let vault_seeds: &[&[u8]] = &[BID_VAULT_SEED, mint.as_ref(), &vault_bump];
+
+        _transfer_token_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            auction.to_account_info(),
+            1,
+            &[auction_seeds],
+        )?;
+        msg!("Token Returned!!!");
+
+        _close_token_account_signed(
+            token_program.clone(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            auction.to_account_info(),
+            &[auction_seeds],
+        )?;
+
+        _transfer_lamports_signed(
+            ctx.accounts.system_program.to_account_info(),
+            bid_vault.clone(),
+            ctx.accounts.seller.to_account_info(),
+            bid_vault.lamports(),
+            &[vault_seeds],
+        )?;
+        msg!("Auction Cancelled!!!");
+
+        emit!(AuctionCancelled {
+            mint,
+            seller: auction.seller,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    // endregion
+
+    // region: Dutch Auctions
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_dutch_sale(
+        ctx: Context<CreateDutchSale>,
+        sale_id: u64,
+        start_price: u64,
+        floor_price: u64,
+        start_time: i64,
+        price_drop: u64,
+        drop_interval: i64,
+        max_items: u32,
+        rebate: bool,
+        metadata_title: String,
+        metadata_symbol: String,
+        metadata_uri: String,
+        creators: Vec<NFTCreator>,
+        seller_fee_basis_points: u16,
+    ) -> Result<()> {
+        /*
+         *  [Create Dutch Sale]
+         *
+         *   - The price starts at start_price and drops by price_drop every drop_interval seconds
+         *     after start_time, until it reaches floor_price.
+         *   - Every item is minted on purchase through the create_nft path, with the program PDA as
+         *     authority. Items are titled "<title> #<number>" and share the symbol, uri and royalty.
+         *   - Empty symbol and creators fall back to the config at mint time, same as create_nft.
+         *     Empty creators are rejected up front when the config has no creators to fall back to.
+         *   - With rebate, payments wait in the sale vault. Once the sale is over every buyer can claim
+         *     back what they paid above the clearing price, the price of the last item sold.
+         *   - The vault is funded with its rent exempt minimum by the seller, same as the bid vault.
+         *     The seller gets it back, together with the rent of the sale, through close_dutch_sale.
+         */
+
+        require!(
+            start_price > 0 && floor_price <= start_price && drop_interval > 0 && max_items > 0,
+            ErrorCode::InvalidSaleParams
+        );
+        require!(
+            seller_fee_basis_points <= 10000,
+            ErrorCode::InvalidSellerFeeBasisPoints
+        );
+        // The longest title the sale will ever mint has to fit
+        _validate_metadata_title(&format!("{} #{}", metadata_title, max_items))?;
+        if !metadata_symbol.is_empty() {
+            _validate_metadata_symbol(&metadata_symbol)?;
+        }
+        _validate_metadata_uri(&metadata_uri)?;
+
+        let config = ProgramConfig::load(&ctx.accounts.config)?;
+        if let Some(config) = &config {
+            require!(
+                config.can_mint(&ctx.accounts.authority.key()),
+                ErrorCode::MinterNotAllowed
+            );
+        }
+        // Empty creators only work when the config has creators to fill in, otherwise every
+        // purchase would fail at mint time
+        let has_default_creators = matches!(&config, Some(config) if !config.creators.is_empty());
+        if !creators.is_empty() || !has_default_creators {
+            _validate_creators(&creators)?;
+        }
+
+        let sale = &mut ctx.accounts.sale;
+        sale.authority = ctx.accounts.authority.key();
+        sale.sale_id = sale_id;
+        sale.start_price = start_price;
+        sale.floor_price = floor_price;
+        sale.start_time = start_time;
+        sale.price_drop = price_drop;
+        sale.drop_interval = drop_interval;
+        sale.max_items = max_items;
+        sale.sold = 0;
+        sale.rebate = rebate;
+        sale.last_price = 0;
+        sale.ended = false;
+        sale.open_receipts = 0;
+        sale.title = metadata_title;
+        sale.symbol = metadata_symbol;
+        sale.uri = metadata_uri;
+        sale.creators = creators;
+        sale.seller_fee_basis_points = seller_fee_basis_points;
+        sale.bump = *ctx.bumps.get("sale").unwrap();
+        sale.vault_bump = *ctx.bumps.get("sale_vault").unwrap();
+
+        _transfer_lamports(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.sale_vault.to_account_info(),
+            Rent::get()?.minimum_balance(0),
+        )?;
+        msg!("Dutch Sale Created!!!");
+
+        emit!(DutchSaleCreated {
+            sale: sale.key(),
+            authority: sale.authority,
+            start_price,
+            floor_price,
+            start_time,
+            max_items,
+            rebate,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn buy_dutch_sale(ctx: Context<BuyDutchSale>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let sale = &mut ctx.accounts.sale;
+
+        /*
+         *  [Buy Dutch Sale]
+         *
+         *   - The price is computed from the clock, the buyer pays it and the NFT is minted into
+         *     their wallet. The buyer signs and pays for everything, including the config mint price.
+         *   - Without rebate the seller is paid right away, with rebate the payment goes to the vault
+         *     and the receipt of the buyer keeps track of it. The receipt is only created for rebate
+         *     sales, without rebate its address is passed but never touched.
+         */
+
+        require!(now >= sale.start_time, ErrorCode::SaleNotStarted);
+        require!(!sale.is_over(), ErrorCode::SaleEnded);
+
+        let price = sale.current_price(now);
+        _transfer_lamports(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            if sale.rebate {
+                ctx.accounts.sale_vault.to_account_info()
+            } else {
+                ctx.accounts.authority.to_account_info()
+            },
+            price,
+        )?;
+        msg!("Sale Price Paid!!!");
+
+        sale.sold += 1;
+        sale.last_price = price;
+
+        // Only rebate sales have anything to pay back, the receipt is left alone otherwise
+        if sale.rebate {
+            let receipt_account = ctx.accounts.receipt.to_account_info();
+            let sale_key = sale.key();
+            let buyer_key = ctx.accounts.buyer.key();
+            let receipt_bump = *ctx.bumps.get("receipt").unwrap();
+
+            let mut receipt = if receipt_account.data_is_empty() {
+                let bump = [receipt_bump];
+                let receipt_seeds: &[&[u8]] = &[
+                    DUTCH_RECEIPT_SEED,
+                    sale_key.as_ref(),
+                    buyer_key.as_ref(),
+                    &bump,
+                ];
+                _create_program_account_signed(
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.buyer.to_account_info(),
+                    receipt_account.clone(),
+                    DutchReceipt::LEN,
+                    &[receipt_seeds],
+                )?;
+                sale.open_receipts += 1;
+
+                DutchReceipt {
+                    sale: sale_key,
+                    buyer: buyer_key,
+                    paid: 0,
+                    count: 0,
+                    bump: receipt_bump,
+                }
+            } else {
+                Account::<DutchReceipt>::try_from(&receipt_account)?.into_inner()
+            };
+
+            receipt.paid = receipt
+                .paid
+                .checked_add(price)
+                .ok_or(ErrorCode::InvalidSaleParams)?;
+            receipt.count += 1;
+            receipt.try_serialize(&mut &mut receipt_account.try_borrow_mut_data()?[..])?;
+        }
+
+        CreateNFT {
+            authority_account: ctx.accounts.buyer.clone(),
+            payer: ctx.accounts.buyer.clone(),
+            minter_account: UncheckedAccount::try_from(
+                ctx.accounts.minter_account.to_account_info(),
+            ),
+            recipient: UncheckedAccount::try_from(ctx.accounts.buyer.to_account_info()),
+            token_holder_account: ctx.accounts.token_holder_account.clone(),
+            metadata_account: ctx.accounts.metadata_account.clone(),
+            master_edition_account: ctx.accounts.master_edition_account.clone(),
+            program_authority: ctx.accounts.program_authority.clone(),
+            config: ctx.accounts.config.clone(),
+            treasury: ctx.accounts.treasury.clone(),
+            payment_token_account: ctx.accounts.payment_token_account.clone(),
+            treasury_token_account: ctx.accounts.treasury_token_account.clone(),
+            token_program: ctx.accounts.token_program.clone(),
+            associated_token_program: ctx.accounts.associated_token_program.clone(),
+            rent: ctx.accounts.rent.clone(),
+            system_program: ctx.accounts.system_program.clone(),
+            token_metadata_program: ctx.accounts.token_metadata_program.clone(),
+        }
+        .create_as(
+            sale.authority,
+            *ctx.bumps.get("program_authority").unwrap(),
+            &[],
+            format!("{} #{}", sale.title, sale.sold),
+            sale.symbol.clone(),
+            sale.uri.clone(),
+            sale.creators.clone(),
+            sale.seller_fee_basis_points,
+            Some(0), // Drop items can't be printed
+            None,
+            true,
+        )?;
+        msg!("Dutch Sale Item Bought!!!");
+
+        emit!(DutchSaleBought {
+            sale: sale.key(),
+            mint: ctx.accounts.minter_account.key(),
+            buyer: ctx.accounts.buyer.key(),
+            price,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn end_dutch_sale(ctx: Context<EndDutchSale>) -> Result<()> {
+        let sale = &mut ctx.accounts.sale;
+
+        /*
+         *  [End Dutch Sale]
+         *
+         *   - Only the seller can end the sale, at any time, sold out or not.
+         *   - The price of the last item sold becomes the clearing price.
+         *   - With rebate the seller is paid the clearing price for every item, what is left in
+         *     the vault above that is claimed back by the buyers through claim_dutch_rebate.
+         */
+
+        require!(!sale.ended, ErrorCode::SaleEnded);
+        sale.ended = true;
+
+        if sale.rebate && sale.sold > 0 {
+            let sale_key = sale.key();
+            let vault_bump = [sale.vault_bump];
+            let vault_seeds: &[&[u8]] = &[DUTCH_VAULT_SEED, sale_key.as_ref(), &vault_bump];
+
+            _transfer_lamports_signed(
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.sale_vault.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                sale.last_price
+                    .checked_mul(sale.sold as u64)
+                    .ok_or(ErrorCode::InvalidSaleParams)?,
+                &[vault_seeds],
+            )?;
+            msg!("Seller Paid!!!");
+        }
+        msg!("Dutch Sale Ended!!!");
+
+        emit!(DutchSaleEnded {
+            sale: sale.key(),
+            sold: sale.sold,
+            clearing_price: sale.last_price,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_dutch_rebate(ctx: Context<ClaimDutchRebate>) -> Result<()> {
+        let sale = &mut ctx.accounts.sale;
+        let receipt = &ctx.accounts.receipt;
+
+        // The clearing price is only final once nothing can be sold anymore. Anyone can send the
+        // claim, the rebate and the receipt rent always go to the buyer, so the seller can close out
+        // the receipts of buyers who never claim and then close the sale.
+        require!(sale.is_over(), ErrorCode::SaleNotEnded);
+        sale.open_receipts -= 1;
+
+        let amount = receipt
+            .paid
+            .checked_sub(
+                sale.last_price
+                    .checked_mul(receipt.count as u64)
+                    .ok_or(ErrorCode::InvalidSaleParams)?,
+            )
+            .ok_or(ErrorCode::InvalidSaleParams)?;
+
+        if amount > 0 {
+            let sale_key = sale.key();
+            let vault_bump = [sale.vault_bump];
+            let vault_seeds: &[&[u8]] = &[DUTCH_VAULT_SEED, sale_key.as_ref(), &vault_bump];
+
+            _transfer_lamports_signed(
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.sale_vault.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                amount,
+                &[vault_seeds],
+            )?;
+        }
+        msg!("Rebate Claimed!!!");
+
+        emit!(DutchRebateClaimed {
+            sale: sale.key(),
+            buyer: receipt.buyer,
+            amount,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn close_dutch_sale(ctx: Context<CloseDutchSale>) -> Result<()> {
+        let sale = &ctx.accounts.sale;
+        let sale_vault = ctx.accounts.sale_vault.to_account_info();
+
+        // Every receipt has to be claimed first, the vault and the sale are needed for that.
+        // The seller can claim for buyers who don't, see claim_dutch_rebate.
+        require!(sale.ended, ErrorCode::SaleNotEnded);
+        require!(sale.open_receipts == 0, ErrorCode::OpenReceipts);
+
+        let sale_key = sale.key();
+        let vault_bump = [sale.vault_bump];
+        let vault_seeds: &[&[u8]] = &[DUTCH_VAULT_SEED, sale_key.as_ref(), &vault_bump];
+
+        // Whatever is left is the rent exempt minimum the seller put in
+        _transfer_lamports_signed(
+            ctx.accounts.system_program.to_account_info(),
+            sale_vault.clone(),
+            ctx.accounts.authority.to_account_info(),
+            sale_vault.lamports(),
+            &[vault_seeds],
+        )?;
+        msg!("Dutch Sale Closed!!!");
+
+        Ok(())
+    }
+
+    // endregion
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub owner_account: Signer<'info>, // This is you
+
+    #[account(mut)]
+    pub minter_account: Signer<'info>, // The mint account that will hold the token.
+
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateNFTHolder<'info> {
+    pub user_account: Signer<'info>, // This is you
+
+    #[account(mut)]
+    pub payer: Signer<'info>, // Pays the rent, can be a relayer or the user itself
+
+    #[account(mut)]
+    pub minter_account: Account<'info, Mint>, // The mint account that will execute token logics.
+
+    /// CHECK: Used by metaplex
+    #[account(mut)]
+    pub token_holder_account: UncheckedAccount<'info>, // This wallet will hold tokens representing the user
+    // #[account(
+    //     init_if_needed,
+    //     payer = user_account,
+    //     associated_token::mint = minter_account,
+    //     associated_token::authority = user_account
+    // )]
+    // pub token_holder_account: Account<'info, TokenAccount>, // This wallet will hold tokens representing the user
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintNFT<'info> {
+    pub authority_account: Signer<'info>, // This is you
+
+    #[account(mut)]
+    pub payer: Signer<'info>, // Pays the rent, can be a relayer or the authority itself
+
+    #[account(mut)]
+    pub minter_account: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub token_holder_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), minter_account.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_ID.as_ref(),
+            minter_account.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub master_edition_account: UncheckedAccount<'info>,
+
+    /// CHECK: Config PDA of this program, it is only enforced once initialize_config was called.
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferNFT<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>, // This is you
+
+    #[account(
+        constraint = mint.decimals == 0 && mint.supply == 1 @ ErrorCode::NotAnNFT,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = sender.mint == mint.key() @ ErrorCode::InvalidTokenMint,
+        constraint = sender.owner == authority.key() @ ErrorCode::InvalidTokenOwner,
+        constraint = sender.amount == 1 @ ErrorCode::InsufficientTokenBalance,
+    )]
+    pub sender: Account<'info, TokenAccount>,
+
+    /// CHECK: Only used as the owner of the recipient token account.
+    pub recipient_wallet: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = recipient_wallet,
+        constraint = recipient.key() != sender.key() @ ErrorCode::SameTokenAccount,
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>, // Pays for the recipient token account if it has to be created, can be the authority
+
+    /// CHECK: Only has to exist; master editions and print editions share this address.
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), mint.key().as_ref(), b"edition"],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+        constraint = edition_account.owner == &TOKEN_METADATA_ID @ ErrorCode::MissingMasterEdition,
+    )]
+    pub edition_account: UncheckedAccount<'info>,
+
+    /// CHECK: Config PDA of this program, it is only enforced once initialize_config was called.
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateNFT<'info> {
+    // Writable because Token Metadata wants a writable collection authority, it pays for nothing
+    #[account(mut)]
+    pub authority_account: Signer<'info>, // This is you
+
+    #[account(mut)]
+    pub payer: Signer<'info>, // Pays the rent, can be a relayer or the authority itself
+
+    /// CHECK: The mint account that will hold the token. Created here, so it has to sign,
+    /// or be a PDA of this program that the program signs for.
+    #[account(mut)]
+    pub minter_account: UncheckedAccount<'info>,
+
+    /// CHECK: Wallet that receives the NFT, pass the authority itself to keep it.
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Created via metaplex.
+    #[account(mut)]
+    pub token_holder_account: UncheckedAccount<'info>,
+
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), minter_account.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_ID.as_ref(),
+            minter_account.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub master_edition_account: UncheckedAccount<'info>,
+
+    /// CHECK: PDA of this program and the authority, only used as mint, freeze and update authority of program owned NFTs.
+    /// Writable because Token Metadata wants a writable collection authority.
+    #[account(mut, seeds = [PROGRAM_AUTHORITY_SEED, authority_account.key().as_ref()], bump)]
+    pub program_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Config PDA of this program, it is only enforced once initialize_config was called.
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Lamports only PDA of this program, receives the config mint price.
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Payer's token account for the config token price, only read when a token price is set.
+    #[account(mut)]
+    pub payment_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury's token account for the config token price, only read when a token price is set.
+    #[account(mut)]
+    pub treasury_token_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateNFTMetadata<'info> {
+    pub authority_account: Signer<'info>, // This is you
+
+    pub minter_account: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), minter_account.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+        constraint = metadata_account.update_authority == authority_account.key()
+            || metadata_account.update_authority == program_authority.key() @ ErrorCode::InvalidUpdateAuthority,
+    )]
+    pub metadata_account: Account<'info, MetadataAccount>, // Derived from the Token Metadata program and the mint.
+
+    /// CHECK: PDA of this program and the authority, signs for NFTs it is the update authority of.
+    #[account(seeds = [PROGRAM_AUTHORITY_SEED, authority_account.key().as_ref()], bump)]
+    pub program_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Config PDA of this program, it is only enforced once initialize_config was called.
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // This is you, the upgrade authority, becomes the admin of the program
+
+    #[account(
+        init,
+        payer = admin,
+        space = ProgramConfig::LEN,
+        seeds = [CONFIG_SEED],
+        bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: Lamports only PDA of this program, collects the mint fees.
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::InvalidProgramData,
+    )]
+    pub program: Program<'info, crate::program::R21MetaplexTests>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::InvalidAdmin,
+    )]
+    pub program_data: Account<'info, ProgramData>, // Holds the upgrade authority of this program
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // Pays for the bigger account
+
+    /// CHECK: Config PDA in an older layout, it can't be loaded as ProgramConfig before the migration.
+    #[account(mut, seeds = [CONFIG_SEED], bump, owner = crate::ID)]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: Lamports only PDA of this program, collects the mint fees.
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Any wallet the admin wants the lamports in.
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasuryTokens<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: Lamports only PDA of this program, owns the treasury token accounts.
+    #[account(seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == treasury.key() @ ErrorCode::InvalidTreasuryTokenAccount,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == treasury_token_account.mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub authority: Signer<'info>, // The admin or the guardian
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = authority.key() == config.admin
+            || authority.key() == config.guardian @ ErrorCode::InvalidGuardian,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateNFTInCollection<'info> {
+    pub nft: CreateNFT<'info>, // The accounts of the NFT being created.
+
+    pub collection_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), collection_mint.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+        constraint = collection_metadata.collection_details.is_some() @ ErrorCode::NotASizedCollection,
+    )]
+    pub collection_metadata: Account<'info, MetadataAccount>, // Its size is updated by metaplex.
+
+    #[account(
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_ID.as_ref(),
+            collection_mint.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub collection_master_edition: Account<'info, MasterEditionAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CreateNFTBatch<'info> {
+    pub authority_account: Signer<'info>, // This is you
+
+    #[account(mut)]
+    pub payer: Signer<'info>, // Pays the rent, can be a relayer or the authority itself
+
+    /// CHECK: Wallet that receives every NFT of the batch, pass the authority itself to keep them.
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: PDA of this program and the authority, only used as mint, freeze and update authority of program owned NFTs.
+    #[account(seeds = [PROGRAM_AUTHORITY_SEED, authority_account.key().as_ref()], bump)]
+    pub program_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Config PDA of this program, it is only enforced once initialize_config was called.
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Lamports only PDA of this program, receives the config mint price.
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Payer's token account for the config token price, only read when a token price is set.
+    #[account(mut)]
+    pub payment_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury's token account for the config token price, only read when a token price is set.
+    #[account(mut)]
+    pub treasury_token_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProgramAuthority<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>, // This is you
+
+    /// CHECK: PDA of this program and the authority, collects the royalties it is a creator of.
+    #[account(mut, seeds = [PROGRAM_AUTHORITY_SEED, authority.key().as_ref()], bump)]
+    pub program_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PrintEdition<'info> {
+    #[account(mut)]
+    pub authority_account: Signer<'info>, // This is you, the holder of the master edition
+
+    #[account(mut)]
+    pub minter_account: Signer<'info>, // The mint account that will hold the edition token.
+
+    /// CHECK: Created via metaplex.
+    #[account(mut)]
+    pub token_holder_account: UncheckedAccount<'info>,
+
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), minter_account.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_ID.as_ref(),
+            minter_account.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub edition_account: UncheckedAccount<'info>,
+
+    /// CHECK: Created via metaplex if this is the first edition of its marker range.
+    #[account(mut)]
+    pub edition_marker_account: UncheckedAccount<'info>,
+
+    pub master_minter_account: Account<'info, Mint>,
+
+    pub master_token_holder_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), master_minter_account.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub master_metadata_account: Account<'info, MetadataAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_ID.as_ref(),
+            master_minter_account.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+        constraint = master_edition_account.max_supply.unwrap_or(u64::MAX) > master_edition_account.supply @ ErrorCode::MaxSupplyReached,
+    )]
+    pub master_edition_account: Account<'info, MasterEditionAccount>, // Its supply is incremented by metaplex.
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BurnNFT<'info> {
+    #[account(mut)]
+    pub authority_account: Signer<'info>, // This is you, the holder of the NFT
+
+    #[account(mut)]
+    pub minter_account: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub token_holder_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), minter_account.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub metadata_account: Account<'info, MetadataAccount>, // Closed via metaplex.
+
+    /// CHECK: Closed via metaplex. Either a master edition or a print edition. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_ID.as_ref(),
+            minter_account.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub edition_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ListNFT<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>, // This is you
+
+    #[account(
+        constraint = mint.decimals == 0 && mint.supply == 1 @ ErrorCode::NotAnNFT,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == mint.key() @ ErrorCode::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ ErrorCode::InvalidTokenOwner,
+        constraint = seller_token_account.amount == 1 @ ErrorCode::InsufficientTokenBalance,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = Listing::LEN,
+        seeds = [LISTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        init,
+        payer = seller,
+        seeds = [ESCROW_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = listing,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyNFT<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>, // This is you
+
+    /// CHECK: Only receives the price and the rent, checked against the listing.
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), mint.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub metadata_account: Account<'info, MetadataAccount>, // Royalty and creators of the NFT
+
+    #[account(
+        mut,
+        seeds = [LISTING_SEED, mint.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::InvalidSeller,
+        has_one = mint @ ErrorCode::InvalidTokenMint,
+        constraint = listing.mode == ListingMode::Escrow @ ErrorCode::InvalidListingMode,
+        close = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>, // This is you
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [LISTING_SEED, mint.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::InvalidSeller,
+        has_one = mint @ ErrorCode::InvalidTokenMint,
+        constraint = listing.mode == ListingMode::Escrow @ ErrorCode::InvalidListingMode,
+        close = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ListNFTDelegated<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>, // This is you
+
+    #[account(
+        constraint = mint.decimals == 0 && mint.supply == 1 @ ErrorCode::NotAnNFT,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: Freeze authority of the mint, Token Metadata checks it during the CPI.
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), mint.key().as_ref(), b"edition"],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub edition_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == mint.key() @ ErrorCode::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ ErrorCode::InvalidTokenOwner,
+        constraint = seller_token_account.amount == 1 @ ErrorCode::InsufficientTokenBalance,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = Listing::LEN,
+        seeds = [LISTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateNFTHolder<'info> {
+pub struct BuyNFTDelegated<'info> {
     #[account(mut)]
-    pub user_account: Signer<'info>, // This is you
+    pub buyer: Signer<'info>, // This is you
 
+    /// CHECK: Only receives the price and the rent, checked against the listing.
     #[account(mut)]
-    pub minter_account: Account<'info, Mint>, // The mint account that will execute token logics.
+    pub seller: UncheckedAccount<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), mint.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub metadata_account: Account<'info, MetadataAccount>, // Royalty and creators of the NFT
+
+    /// CHECK: Freeze authority of the mint, Token Metadata checks it during the CPI.
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), mint.key().as_ref(), b"edition"],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub edition_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [LISTING_SEED, mint.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::InvalidSeller,
+        has_one = mint @ ErrorCode::InvalidTokenMint,
+        constraint = listing.mode == ListingMode::Delegated @ ErrorCode::InvalidListingMode,
+        close = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == mint.key() @ ErrorCode::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ ErrorCode::InvalidTokenOwner,
+        constraint = seller_token_account.delegate == COption::Some(listing.key()) @ ErrorCode::InvalidListingMode,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
 
-    /// CHECK: Used by metaplex
-    #[account(mut)]
-    pub token_holder_account: UncheckedAccount<'info>, // This wallet will hold tokens representing the user
-    // #[account(
-    //     init_if_needed,
-    //     payer = user_account,
-    //     associated_token::mint = minter_account,
-    //     associated_token::authority = user_account
-    // )]
-    // pub token_holder_account: Account<'info, TokenAccount>, // This wallet will hold tokens representing the user
     pub token_program: Program<'info, Token>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct MintNFT<'info> {
+pub struct CancelDelegatedListing<'info> {
     #[account(mut)]
-    pub authority_account: Signer<'info>, // This is you
+    pub seller: Signer<'info>, // This is you
 
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: Freeze authority of the mint, Token Metadata checks it during the CPI.
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), mint.key().as_ref(), b"edition"],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub edition_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [LISTING_SEED, mint.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::InvalidSeller,
+        has_one = mint @ ErrorCode::InvalidTokenMint,
+        constraint = listing.mode == ListingMode::Delegated @ ErrorCode::InvalidListingMode,
+        close = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == mint.key() @ ErrorCode::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ ErrorCode::InvalidTokenOwner,
+        constraint = seller_token_account.delegate == COption::Some(listing.key()) @ ErrorCode::InvalidListingMode,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAuction<'info> {
     #[account(mut)]
-    pub minter_account: Account<'info, Mint>,
+    pub seller: Signer<'info>, // This is you
+
+    #[account(
+        constraint = mint.decimals == 0 && mint.supply == 1 @ ErrorCode::NotAnNFT,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == mint.key() @ ErrorCode::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ ErrorCode::InvalidTokenOwner,
+        constraint = seller_token_account.amount == 1 @ ErrorCode::InsufficientTokenBalance,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = Auction::LEN,
+        seeds = [AUCTION_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        init,
+        payer = seller,
+        seeds = [AUCTION_ESCROW_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = auction,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
+    /// CHECK: Lamports only PDA of this program, holds the highest bid.
+    #[account(mut, seeds = [BID_VAULT_SEED, mint.key().as_ref()], bump)]
+    pub bid_vault: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
     #[account(mut)]
-    pub token_holder_account: Account<'info, TokenAccount>,
+    pub bidder: Signer<'info>, // This is you
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, mint.key().as_ref()],
+        bump = auction.bump,
+        has_one = mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// CHECK: Lamports only PDA of this program, holds the highest bid.
+    #[account(mut, seeds = [BID_VAULT_SEED, mint.key().as_ref()], bump = auction.vault_bump)]
+    pub bid_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Gets the previous highest bid back, only read when there is one.
+    #[account(
+        mut,
+        constraint = auction.highest_bid == 0
+            || previous_bidder.key() == auction.highest_bidder @ ErrorCode::InvalidPreviousBidder,
+    )]
+    pub previous_bidder: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
 
-    /// CHECK: Created via metaplex. Should be replaced soon by its appropriate Account<> object
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
     #[account(mut)]
-    pub metadata_account: UncheckedAccount<'info>,
+    pub payer: Signer<'info>, // Anyone, pays for the winner's token account if needed
 
-    /// CHECK: Created via metaplex. Should be replaced soon by its appropriate Account<> object
+    /// CHECK: Receives the proceeds and the rent, checked against the auction.
     #[account(mut)]
-    pub master_edition_account: UncheckedAccount<'info>,
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK: The highest bidder, or the seller when nobody bid.
+    #[account(
+        constraint = winner.key() == auction.winner() @ ErrorCode::InvalidWinner,
+    )]
+    pub winner: UncheckedAccount<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), mint.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
+    pub metadata_account: Account<'info, MetadataAccount>, // Royalty and creators of the NFT
+
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, mint.key().as_ref()],
+        bump = auction.bump,
+        has_one = seller @ ErrorCode::InvalidSeller,
+        has_one = mint @ ErrorCode::InvalidTokenMint,
+        close = seller,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        seeds = [AUCTION_ESCROW_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Lamports only PDA of this program, holds the highest bid.
+    #[account(mut, seeds = [BID_VAULT_SEED, mint.key().as_ref()], bump = auction.vault_bump)]
+    pub bid_vault: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = winner,
+    )]
+    pub winner_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
-    /// CHECK: Metaplex will check this
-    pub token_metadata_program: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct TransferNFT<'info> {
+pub struct CancelAuction<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>, // This is you
+    pub seller: Signer<'info>, // This is you
 
-    #[account(mut)]
     pub mint: Account<'info, Mint>,
 
-    #[account(mut)]
-    pub sender: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, mint.key().as_ref()],
+        bump = auction.bump,
+        has_one = seller @ ErrorCode::InvalidSeller,
+        has_one = mint @ ErrorCode::InvalidTokenMint,
+        close = seller,
+    )]
+    pub auction: Account<'info, Auction>,
 
-    #[account(mut)]
-    pub recipient: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [AUCTION_ESCROW_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Lamports only PDA of this program, holds the highest bid.
+    #[account(mut, seeds = [BID_VAULT_SEED, mint.key().as_ref()], bump = auction.vault_bump)]
+    pub bid_vault: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateNFT<'info> {
+#[instruction(sale_id: u64)]
+pub struct CreateDutchSale<'info> {
     #[account(mut)]
-    pub authority_account: Signer<'info>, // This is you
+    pub authority: Signer<'info>, // This is you, the seller
+
+    #[account(
+        init,
+        payer = authority,
+        space = DutchSale::LEN,
+        seeds = [DUTCH_SALE_SEED, authority.key().as_ref(), &sale_id.to_le_bytes()],
+        bump,
+    )]
+    pub sale: Account<'info, DutchSale>,
+
+    /// CHECK: Lamports only PDA of this program, holds the payments of rebate sales.
+    #[account(mut, seeds = [DUTCH_VAULT_SEED, sale.key().as_ref()], bump)]
+    pub sale_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Config PDA of this program, it is only enforced once initialize_config was called.
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyDutchSale<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>, // This is you, pays the price and the rent
+
+    /// CHECK: The seller, paid right away when the sale has no rebate. Checked against the sale.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub minter_account: Signer<'info>, // The mint account that will hold the token.
@@ -586,31 +4334,771 @@ pub struct CreateNFT<'info> {
     #[account(mut)]
     pub token_holder_account: UncheckedAccount<'info>,
 
-    /// CHECK: Created via metaplex.
-    #[account(mut)]
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_ID.as_ref(), minter_account.key().as_ref()],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
     pub metadata_account: UncheckedAccount<'info>,
 
-    /// CHECK: Created via metaplex. Should be replaced soon by its appropriate Account<> object
-    #[account(mut)]
+    /// CHECK: Created via metaplex. Derived from the Token Metadata program and the mint.
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_ID.as_ref(),
+            minter_account.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = TOKEN_METADATA_ID,
+        bump,
+    )]
     pub master_edition_account: UncheckedAccount<'info>,
 
+    #[account(
+        mut,
+        seeds = [DUTCH_SALE_SEED, sale.authority.as_ref(), &sale.sale_id.to_le_bytes()],
+        bump = sale.bump,
+        has_one = authority @ ErrorCode::InvalidSeller,
+    )]
+    pub sale: Account<'info, DutchSale>,
+
+    /// CHECK: Lamports only PDA of this program, holds the payments of rebate sales.
+    #[account(mut, seeds = [DUTCH_VAULT_SEED, sale.key().as_ref()], bump = sale.vault_bump)]
+    pub sale_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Receipt PDA of this program, only created and written for rebate sales.
+    #[account(
+        mut,
+        seeds = [DUTCH_RECEIPT_SEED, sale.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub receipt: UncheckedAccount<'info>,
+
+    /// CHECK: PDA of this program and the seller, mint, freeze and update authority of every item of the sale.
+    #[account(seeds = [PROGRAM_AUTHORITY_SEED, authority.key().as_ref()], bump)]
+    pub program_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Config PDA of this program, it is only enforced once initialize_config was called.
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Lamports only PDA of this program, receives the config mint price.
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer's token account for the config token price, only read when a token price is set.
+    #[account(mut)]
+    pub payment_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury's token account for the config token price, only read when a token price is set.
+    #[account(mut)]
+    pub treasury_token_account: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
-    /// CHECK: Metaplex will check this
+    /// CHECK: Only used as the target of the CPIs, its address is checked.
+    #[account(address = TOKEN_METADATA_ID)]
     pub token_metadata_program: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateNFTMetadata<'info> {
+pub struct EndDutchSale<'info> {
     #[account(mut)]
-    pub authority_account: Signer<'info>, // This is you
+    pub authority: Signer<'info>, // This is you, the seller
 
-    /// CHECK: Created via metaplex.
+    #[account(
+        mut,
+        seeds = [DUTCH_SALE_SEED, authority.key().as_ref(), &sale.sale_id.to_le_bytes()],
+        bump = sale.bump,
+        has_one = authority @ ErrorCode::InvalidSeller,
+    )]
+    pub sale: Account<'info, DutchSale>,
+
+    /// CHECK: Lamports only PDA of this program, holds the payments of rebate sales.
+    #[account(mut, seeds = [DUTCH_VAULT_SEED, sale.key().as_ref()], bump = sale.vault_bump)]
+    pub sale_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDutchRebate<'info> {
+    /// CHECK: Owner of the receipt, gets the rebate and the receipt rent. Does not have to sign,
+    /// so the seller can close out receipts that are never claimed.
     #[account(mut)]
-    pub metadata_account: UncheckedAccount<'info>,
+    pub buyer: UncheckedAccount<'info>,
 
-    /// CHECK: Checked by metaplex.
-    pub token_metadata_program: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [DUTCH_SALE_SEED, sale.authority.as_ref(), &sale.sale_id.to_le_bytes()],
+        bump = sale.bump,
+    )]
+    pub sale: Account<'info, DutchSale>,
+
+    /// CHECK: Lamports only PDA of this program, holds the payments of rebate sales.
+    #[account(mut, seeds = [DUTCH_VAULT_SEED, sale.key().as_ref()], bump = sale.vault_bump)]
+    pub sale_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [DUTCH_RECEIPT_SEED, sale.key().as_ref(), buyer.key().as_ref()],
+        bump = receipt.bump,
+        close = buyer,
+    )]
+    pub receipt: Account<'info, DutchReceipt>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDutchSale<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>, // This is you, the seller
+
+    #[account(
+        mut,
+        seeds = [DUTCH_SALE_SEED, authority.key().as_ref(), &sale.sale_id.to_le_bytes()],
+        bump = sale.bump,
+        has_one = authority @ ErrorCode::InvalidSeller,
+        close = authority,
+    )]
+    pub sale: Account<'info, DutchSale>,
+
+    /// CHECK: Lamports only PDA of this program, holds the payments of rebate sales.
+    #[account(mut, seeds = [DUTCH_VAULT_SEED, sale.key().as_ref()], bump = sale.vault_bump)]
+    pub sale_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Read-only view of a Token Metadata `Metadata` account, so it can be used as `Account<'info, MetadataAccount>`.
+/// Nothing is ever written back, the account is owned by the Token Metadata program.
+#[derive(Clone)]
+pub struct MetadataAccount(mpl_token_metadata::state::Metadata);
+
+impl AccountDeserialize for MetadataAccount {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        // Checks the Token Metadata key byte so another kind of metaplex account is rejected
+        mpl_token_metadata::state::Metadata::safe_deserialize(buf)
+            .map(MetadataAccount)
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        mpl_token_metadata::state::Metadata::deserialize(buf)
+            .map(MetadataAccount)
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))
+    }
+}
+
+impl AccountSerialize for MetadataAccount {}
+
+impl Owner for MetadataAccount {
+    fn owner() -> Pubkey {
+        TOKEN_METADATA_ID
+    }
+}
+
+impl Deref for MetadataAccount {
+    type Target = mpl_token_metadata::state::Metadata;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Read-only view of a Token Metadata `MasterEditionV2` account, so it can be used as `Account<'info, MasterEditionAccount>`.
+#[derive(Clone)]
+pub struct MasterEditionAccount(mpl_token_metadata::state::MasterEditionV2);
+
+impl AccountDeserialize for MasterEditionAccount {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        // Checks the Token Metadata key byte so print editions are rejected
+        mpl_token_metadata::state::MasterEditionV2::safe_deserialize(buf)
+            .map(MasterEditionAccount)
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        mpl_token_metadata::state::MasterEditionV2::deserialize(buf)
+            .map(MasterEditionAccount)
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))
+    }
+}
+
+impl AccountSerialize for MasterEditionAccount {}
+
+impl Owner for MasterEditionAccount {
+    fn owner() -> Pubkey {
+        TOKEN_METADATA_ID
+    }
+}
+
+impl Deref for MasterEditionAccount {
+    type Target = mpl_token_metadata::state::MasterEditionV2;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Program wide settings, managed by the admin.
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,             // Can pause and resume besides the admin
+    pub paused: u8,                   // Bitmask of the PAUSE_* flags
+    pub allowed_minters: Vec<Pubkey>, // Wallets besides the admin that can mint
+    pub seller_fee_basis_points: u16, // Default royalty
+    pub creators: Vec<NFTCreator>,    // Default creators
+    pub symbol: String,               // Default symbol
+    pub mint_price: u64,              // Lamports paid into the treasury on every mint
+    pub token_price_mint: Option<Pubkey>, // Token charged on every mint, None = no token price
+    pub token_price: u64,             // Amount of that token paid into the treasury on every mint
+    pub bump: u8,
+}
+
+impl ProgramConfig {
+    pub const MAX_ALLOWED_MINTERS: usize = 10;
+
+    pub const PAUSE_CREATE: u8 = 1 << 0;
+    pub const PAUSE_MINT: u8 = 1 << 1;
+    pub const PAUSE_TRANSFER: u8 = 1 << 2;
+    pub const PAUSE_UPDATE: u8 = 1 << 3;
+
+    pub const LEN: usize = 8 // Discriminator
+        + 32 // Admin
+        + 32 // Guardian
+        + 1 // Paused
+        + 4 + 32 * Self::MAX_ALLOWED_MINTERS // Allowed Minters
+        + 2 // Seller Fee Basis Points
+        + 4 + (32 + 1) * mpl_token_metadata::state::MAX_CREATOR_LIMIT // Creators
+        + 4 + mpl_token_metadata::state::MAX_SYMBOL_LENGTH // Symbol
+        + 8 // Mint Price
+        + 1 + 32 // Token Price Mint
+        + 8 // Token Price
+        + 1; // Bump
+
+    // Sizes of the older layouts, before the guardian, the mint price and the token price were added
+    pub const LEN_WITHOUT_GUARDIAN: usize = Self::LEN - 32 - 1 - 8 - (1 + 32) - 8;
+    pub const LEN_WITHOUT_MINT_PRICE: usize = Self::LEN - 8 - (1 + 32) - 8;
+    pub const LEN_WITHOUT_TOKEN_PRICE: usize = Self::LEN - (1 + 32) - 8;
+
+    /// Reads a config written in one of the older layouts, recognized by the account size.
+    pub fn from_legacy(data: &[u8]) -> Result<Self> {
+        let (has_guardian, has_mint_price) = match data.len() {
+            Self::LEN_WITHOUT_GUARDIAN => (false, false),
+            Self::LEN_WITHOUT_MINT_PRICE => (true, false),
+            Self::LEN_WITHOUT_TOKEN_PRICE => (true, true),
+            _ => return err!(ErrorCode::InvalidConfigLayout),
+        };
+        require!(
+            data[..8] == <Self as anchor_lang::Discriminator>::discriminator(),
+            ErrorCode::InvalidConfigLayout
+        );
+
+        let data = &mut &data[8..];
+        let admin = Pubkey::deserialize(data)?;
+        let (guardian, paused) = if has_guardian {
+            (Pubkey::deserialize(data)?, u8::deserialize(data)?)
+        } else {
+            (admin, 0)
+        };
+        let allowed_minters = Vec::<Pubkey>::deserialize(data)?;
+        let seller_fee_basis_points = u16::deserialize(data)?;
+        let creators = Vec::<NFTCreator>::deserialize(data)?;
+        let symbol = String::deserialize(data)?;
+        let mint_price = if has_mint_price {
+            u64::deserialize(data)?
+        } else {
+            0
+        };
+        let bump = u8::deserialize(data)?;
+
+        Ok(Self {
+            admin,
+            guardian,
+            paused,
+            allowed_minters,
+            seller_fee_basis_points,
+            creators,
+            symbol,
+            mint_price,
+            token_price_mint: None,
+            token_price: 0,
+            bump,
+        })
+    }
+
+    /// Returns the config if it was initialized, the config is optional for every instruction.
+    pub fn load(account: &AccountInfo) -> Result<Option<Self>> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(
+            &mut &account.try_borrow_data()?[..],
+        )?))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.allowed_minters.len() <= Self::MAX_ALLOWED_MINTERS,
+            ErrorCode::TooManyAllowedMinters
+        );
+        require!(
+            self.seller_fee_basis_points <= 10000,
+            ErrorCode::InvalidSellerFeeBasisPoints
+        );
+        // No default creators is fine, every mint then has to pass its own
+        if !self.creators.is_empty() {
+            mpl_simplified_methods::_validate_creators(&self.creators)?;
+        }
+        mpl_simplified_methods::_validate_metadata_symbol(&self.symbol)
+    }
+
+    /// Fails if the given PAUSE_* flag is set, passes when there is no config.
+    pub fn require_not_paused(account: &AccountInfo, flag: u8) -> Result<()> {
+        if let Some(config) = Self::load(account)? {
+            require!(config.paused & flag == 0, ErrorCode::InstructionPaused);
+        }
+        Ok(())
+    }
+
+    pub fn can_mint(&self, minter: &Pubkey) -> bool {
+        &self.admin == minter || self.allowed_minters.contains(minter)
+    }
+}
+
+/// A fixed price sale, the NFT is locked in escrow or in the seller's wallet until it is bought or the listing is cancelled.
+#[account]
+pub struct Listing {
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64, // Lamports
+    pub mode: ListingMode,
+    pub bump: u8,
+}
+
+impl Listing {
+    pub const LEN: usize = 8 // Discriminator
+        + 32 // Seller
+        + 32 // Mint
+        + 8 // Price
+        + 1 // Mode
+        + 1; // Bump
+}
+
+/// An English auction, the NFT waits in the auction escrow and the highest bid in the bid vault.
+#[account]
+pub struct Auction {
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub reserve_price: u64,     // Lamports, minimum for the first bid
+    pub min_increment: u64,     // Lamports, minimum raise over the highest bid
+    pub end_time: i64,          // Unix timestamp, pushed back by late bids
+    pub extension_window: i64,  // Seconds, bids this close to the end extend it
+    pub highest_bidder: Pubkey, // Pubkey::default() until the first bid
+    pub highest_bid: u64,       // Lamports, 0 until the first bid
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+impl Auction {
+    pub const LEN: usize = 8 // Discriminator
+        + 32 // Seller
+        + 32 // Mint
+        + 8 // Reserve Price
+        + 8 // Min Increment
+        + 8 // End Time
+        + 8 // Extension Window
+        + 32 // Highest Bidder
+        + 8 // Highest Bid
+        + 1 // Bump
+        + 1; // Vault Bump
+
+    /// Whoever gets the NFT on settlement, the seller keeps it when nobody bid.
+    pub fn winner(&self) -> Pubkey {
+        if self.highest_bid > 0 {
+            self.highest_bidder
+        } else {
+            self.seller
+        }
+    }
+}
+
+/// A declining price drop, every purchase mints a new NFT at the price of the moment.
+#[account]
+pub struct DutchSale {
+    pub authority: Pubkey,  // The seller, receives the proceeds
+    pub sale_id: u64,       // Lets a seller run several sales
+    pub start_price: u64,   // Lamports
+    pub floor_price: u64,   // Lamports, the price never drops below it
+    pub start_time: i64,    // Unix timestamp
+    pub price_drop: u64,    // Lamports taken off every drop interval
+    pub drop_interval: i64, // Seconds
+    pub max_items: u32,
+    pub sold: u32,
+    pub rebate: bool, // Early buyers get back what they paid above the clearing price
+    pub last_price: u64, // Lamports, the clearing price once the sale is over
+    pub ended: bool,  // Ended by the seller, a sold out sale is over as well
+    pub open_receipts: u32, // Rebate receipts not claimed yet, the sale can only be closed without any
+    pub title: String,      // Items are titled "<title> #<number>"
+    pub symbol: String,
+    pub uri: String,
+    pub creators: Vec<NFTCreator>,
+    pub seller_fee_basis_points: u16,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+impl DutchSale {
+    pub const LEN: usize = 8 // Discriminator
+        + 32 // Authority
+        + 8 // Sale Id
+        + 8 // Start Price
+        + 8 // Floor Price
+        + 8 // Start Time
+        + 8 // Price Drop
+        + 8 // Drop Interval
+        + 4 // Max Items
+        + 4 // Sold
+        + 1 // Rebate
+        + 8 // Last Price
+        + 1 // Ended
+        + 4 // Open Receipts
+        + 4 + mpl_token_metadata::state::MAX_NAME_LENGTH // Title
+        + 4 + mpl_token_metadata::state::MAX_SYMBOL_LENGTH // Symbol
+        + 4 + mpl_token_metadata::state::MAX_URI_LENGTH // Uri
+        + 4 + (32 + 1) * mpl_token_metadata::state::MAX_CREATOR_LIMIT // Creators
+        + 2 // Seller Fee Basis Points
+        + 1 // Bump
+        + 1; // Vault Bump
+
+    /// Price at the given unix timestamp, one price drop per full interval since the start.
+    pub fn current_price(&self, now: i64) -> u64 {
+        let drops = (now.saturating_sub(self.start_time) / self.drop_interval).max(0) as u64;
+        self.start_price
+            .saturating_sub(self.price_drop.saturating_mul(drops))
+            .max(self.floor_price)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.ended || self.sold >= self.max_items
+    }
+}
+
+/// What one buyer paid in a dutch sale, closed when the rebate is claimed.
+#[account]
+pub struct DutchReceipt {
+    pub sale: Pubkey,
+    pub buyer: Pubkey,
+    pub paid: u64,  // Lamports, over all items bought
+    pub count: u32, // Items bought
+    pub bump: u8,
+}
+
+impl DutchReceipt {
+    pub const LEN: usize = 8 // Discriminator
+        + 32 // Sale
+        + 32 // Buyer
+        + 8 // Paid
+        + 4 // Count
+        + 1; // Bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ListingMode {
+    Escrow,    // The NFT sits in the escrow token account of the listing
+    Delegated, // The NFT stays frozen in the seller's wallet, the listing is its delegate
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RoyaltySplit {
+    pub creator: Pubkey,
+    pub amount: u64, // Lamports
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchNFTMetadata {
+    pub title: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint_bump: u8, // Bump of the mint PDA, derived by the client
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct NFTCreator {
+    pub address: Pubkey,
+    pub share: u8, // Percentage of the royalties, all shares must add up to 100
+}
+
+// region: Events
+
+#[event]
+pub struct NFTCreated {
+    pub mint: Pubkey,
+    pub owner: Pubkey, // Wallet holding the token
+    pub update_authority: Pubkey,
+    pub uri: String,
+    pub max_supply: Option<u64>, // None = unlimited prints
+    pub mint_fee: u64,           // Lamports paid into the treasury
+    pub token_fee: u64,          // Tokens paid into the treasury, in the config token price mint
+    pub slot: u64,
+}
+
+#[event]
+pub struct NFTMetadataUpdated {
+    pub mint: Pubkey,
+    pub update_authority: Pubkey,
+    pub uri: String, // URI after the update
+    pub slot: u64,
+}
+
+#[event]
+pub struct NFTTransferred {
+    pub mint: Pubkey,
+    pub from: Pubkey, // Wallet of the sender
+    pub to: Pubkey,   // Wallet of the recipient
+    pub slot: u64,
+}
+
+#[event]
+pub struct NFTBurned {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub uri: String,
+    pub edition_number: Option<u64>, // None for master editions
+    pub slot: u64,
+}
+
+#[event]
+pub struct NFTPrinted {
+    pub mint: Pubkey,
+    pub master_mint: Pubkey,
+    pub owner: Pubkey,
+    pub uri: String,
+    pub edition_number: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct NFTBatchCreated {
+    pub authority: Pubkey,
+    pub count: u8, // Number of NFTs minted, each one also emits NFTCreated
+    pub slot: u64,
+}
+
+#[event]
+pub struct NFTListed {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64, // Lamports
+    pub slot: u64,
+}
+
+#[event]
+pub struct NFTSold {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64, // Lamports
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoyaltiesPaid {
+    pub mint: Pubkey,
+    pub price: u64,           // Lamports paid by the buyer
+    pub seller_proceeds: u64, // Lamports left for the seller
+    pub splits: Vec<RoyaltySplit>,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuctionCreated {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub reserve_price: u64,
+    pub end_time: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub mint: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,   // Lamports
+    pub end_time: i64, // After a possible extension
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuctionSettled {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub winner: Pubkey, // The seller when nobody bid
+    pub price: u64,     // Lamports, 0 when nobody bid
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuctionCancelled {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DutchSaleCreated {
+    pub sale: Pubkey,
+    pub authority: Pubkey,
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub start_time: i64,
+    pub max_items: u32,
+    pub rebate: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DutchSaleBought {
+    pub sale: Pubkey,
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64, // Lamports
+    pub slot: u64,
+}
+
+#[event]
+pub struct DutchSaleEnded {
+    pub sale: Pubkey,
+    pub sold: u32,
+    pub clearing_price: u64, // Lamports, price of the last item sold
+    pub slot: u64,
+}
+
+#[event]
+pub struct DutchRebateClaimed {
+    pub sale: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64, // Lamports
+    pub slot: u64,
+}
+
+#[event]
+pub struct ListingCancelled {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub slot: u64,
+}
+
+// endregion
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("An NFT can have at most 5 creators")]
+    TooManyCreators,
+    #[msg("Creator shares must add up to 100")]
+    InvalidCreatorShares,
+    #[msg("Seller fee basis points cannot exceed 10000")]
+    InvalidSellerFeeBasisPoints,
+    #[msg("The edition account is neither a master edition nor a print edition")]
+    InvalidEditionAccount,
+    #[msg("Burning a print edition requires the master minter, master token holder, master edition and edition marker accounts")]
+    MissingEditionAccounts,
+    #[msg("Metadata title cannot be empty")]
+    EmptyMetadataTitle,
+    #[msg("Metadata title cannot be longer than 32 bytes")]
+    MetadataTitleTooLong,
+    #[msg("Metadata symbol cannot be longer than 10 bytes")]
+    MetadataSymbolTooLong,
+    #[msg("Metadata URI cannot be empty")]
+    EmptyMetadataUri,
+    #[msg("Metadata URI cannot be longer than 200 bytes")]
+    MetadataUriTooLong,
+    #[msg("Metadata URI must start with https://, http://, ipfs:// or ar://")]
+    InvalidMetadataUriScheme,
+    #[msg("The signer is not the update authority of this metadata")]
+    InvalidUpdateAuthority,
+    #[msg("The collection metadata is not a sized collection parent")]
+    NotASizedCollection,
+    #[msg("Every edition of this master edition has already been printed")]
+    MaxSupplyReached,
+    #[msg("The mint is not an NFT, it needs 0 decimals and a supply of 1")]
+    NotAnNFT,
+    #[msg("The token account does not belong to this mint")]
+    InvalidTokenMint,
+    #[msg("The signer does not own the sender token account")]
+    InvalidTokenOwner,
+    #[msg("The sender token account does not hold the NFT")]
+    InsufficientTokenBalance,
+    #[msg("The sender and recipient token accounts must differ")]
+    SameTokenAccount,
+    #[msg("The mint has no master edition or print edition account")]
+    MissingMasterEdition,
+    #[msg("The config can hold at most 10 allowed minters")]
+    TooManyAllowedMinters,
+    #[msg("The signer is not the admin of the program config")]
+    InvalidAdmin,
+    #[msg("The signer is not allowed to mint by the program config")]
+    MinterNotAllowed,
+    #[msg("The signer is neither the admin nor the guardian of the program config")]
+    InvalidGuardian,
+    #[msg("This instruction is paused")]
+    InstructionPaused,
+    #[msg("The treasury does not hold enough lamports")]
+    InsufficientTreasuryBalance,
+    #[msg("The treasury token account is not owned by the treasury or holds another token")]
+    InvalidTreasuryTokenAccount,
+    #[msg("A batch has to contain 1 to 10 NFTs")]
+    InvalidBatchSize,
+    #[msg(
+        "Every NFT of a batch needs a minter, token holder, metadata and master edition account"
+    )]
+    MissingBatchAccounts,
+    #[msg("The mint of a batch NFT is not the PDA of its batch id, index and bump")]
+    InvalidBatchAccounts,
+    #[msg("The price has to be greater than 0")]
+    InvalidPrice,
+    #[msg("The seller does not match the listing")]
+    InvalidSeller,
+    #[msg("Every creator of the NFT has to be passed as a remaining account, in order")]
+    MissingCreatorAccounts,
+    #[msg("A remaining account does not match the creator at its position")]
+    InvalidCreatorAccount,
+    #[msg("The listing was created in another mode, use the matching instruction")]
+    InvalidListingMode,
+    #[msg("Auctions need a positive duration and minimum increment")]
+    InvalidAuctionParams,
+    #[msg("The auction has already ended")]
+    AuctionEnded,
+    #[msg("The auction has not ended yet")]
+    AuctionNotEnded,
+    #[msg("The bid is below the reserve price or the minimum increment")]
+    BidTooLow,
+    #[msg("The previous bidder does not match the highest bidder of the auction")]
+    InvalidPreviousBidder,
+    #[msg("The winner does not match the highest bidder of the auction")]
+    InvalidWinner,
+    #[msg("The auction already has bids, it can only be settled")]
+    AuctionHasBids,
+    #[msg("A sale needs a start price, a floor below it, a drop interval and at least one item")]
+    InvalidSaleParams,
+    #[msg("The sale has not started yet")]
+    SaleNotStarted,
+    #[msg("The sale has ended or is sold out")]
+    SaleEnded,
+    #[msg("The sale is still running, the clearing price is not final yet")]
+    SaleNotEnded,
+    #[msg("The program data account does not belong to this program")]
+    InvalidProgramData,
+    #[msg("Buyers still have receipts to claim, the sale can't be closed yet")]
+    OpenReceipts,
+    #[msg("The config is not in one of the older layouts, there is nothing to migrate")]
+    InvalidConfigLayout,
 }
@@ -4,6 +4,9 @@ import { PublicKey } from "@solana/web3.js";
 import { R21MetaplexTests } from "../target/types/r21_metaplex_tests";
 import {
   getAssociatedTokenAddress,
+  getOrCreateAssociatedTokenAccount,
+  createMint,
+  mintTo,
   AccountLayout,
   MintLayout,
 } from "@solana/spl-token";
@@ -21,6 +24,31 @@ describe("r21_metaplex_tests", async () => {
     "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
   );
 
+  // PDA used as mint, freeze and update authority of program owned NFTs, one per authority
+  const getProgramAuthority = (authority: PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("authority"), authority.toBuffer()],
+      program.programId
+    )[0];
+
+  // Program data account of the upgradeable program, holds its upgrade authority
+  const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  // PDA holding the program config, only enforced once initialized
+  const [configAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  // Lamports only PDA collecting the mint fees
+  const [treasuryAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+
   const wallet = provider.wallet as Wallet;
   let wallet1Holder: PublicKey;
 
@@ -117,6 +145,7 @@ describe("r21_metaplex_tests", async () => {
         .createNftHolder()
         .accounts({
           userAccount: wallet2.publicKey,
+          payer: wallet2.publicKey,
           minterAccount: minterKeypair.publicKey,
           tokenHolderAccount: wallet2Holder,
         })
@@ -154,13 +183,15 @@ describe("r21_metaplex_tests", async () => {
 
       // Mint Attempt One
       await program.methods
-        .mintNft("TestNFT", "TestNFT", "TestNFT")
+        .mintNft("TestNFT", "TestNFT", "https://arweave.net/TestNFT")
         .accounts({
           authorityAccount: wallet2.publicKey,
+          payer: wallet2.publicKey,
           minterAccount: minterKeypair.publicKey,
           tokenHolderAccount: wallet2Holder,
           metadataAccount: metadataAddress,
           masterEditionAccount: masterEditionAddress,
+          config: configAddress,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         })
         .signers([wallet2.payer])
@@ -193,6 +224,7 @@ describe("r21_metaplex_tests", async () => {
         .createNftHolder()
         .accounts({
           userAccount: wallet3.publicKey,
+          payer: wallet3.publicKey,
           minterAccount: minterKeypair.publicKey,
           tokenHolderAccount: wallet3Holder,
         })
@@ -205,14 +237,30 @@ describe("r21_metaplex_tests", async () => {
         throw new Error("Wallet2 or Wallet3 not found");
       }
 
+      const editionAddress = (
+        await anchor.web3.PublicKey.findProgramAddress(
+          [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+            minterKeypair.publicKey.toBuffer(),
+            Buffer.from("edition"),
+          ],
+          TOKEN_METADATA_PROGRAM_ID
+        )
+      )[0];
+
       // Mint Attempt One
       await program.methods
-        .transferNft()
+        .transferNft(false)
         .accounts({
           authority: wallet2.publicKey,
           mint: minterKeypair.publicKey,
           sender: wallet2Holder,
+          recipientWallet: wallet3.publicKey,
           recipient: wallet3Holder,
+          payer: wallet2.publicKey,
+          editionAccount: editionAddress,
+          config: configAddress,
         })
         .signers([wallet2.payer])
         .rpc();
@@ -243,8 +291,18 @@ describe("r21_metaplex_tests", async () => {
         metadata = {
           name: "TestNFT",
           symbol: "TestNFT",
-          uri: "TestNFT",
-        }
+          uri: "https://arweave.net/TestNFT",
+        },
+        creators = [{ address: mainWallet.publicKey, share: 100 }],
+        sellerFeeBasisPoints = 500,
+        maxSupply: anchor.BN | null = new anchor.BN(1), // null = unlimited prints
+        useProgramAuthority = false,
+        recipient: PublicKey = mainWallet.publicKey, // Wallet the NFT lands in
+        payer: Wallet = mainWallet, // Wallet paying the rent, e.g. a relayer
+        tokenPayment: {
+          paymentTokenAccount: PublicKey;
+          treasuryTokenAccount: PublicKey;
+        } | null = null // Only needed when the config sets a token price
       ) => {
         /**
          * So here is the premise:
@@ -262,7 +320,7 @@ describe("r21_metaplex_tests", async () => {
         // Calculate our token holder address
         const tokenHolder = await getAssociatedTokenAddress(
           minterKeypair.publicKey,
-          mainWallet.publicKey
+          recipient
         );
 
         // Calculate our PDA for the metadata
@@ -292,16 +350,33 @@ describe("r21_metaplex_tests", async () => {
 
         // Minting 1 NFT
         await program.methods
-          .createNft(metadata.name, metadata.symbol, metadata.uri)
+          .createNft(
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+            creators,
+            sellerFeeBasisPoints,
+            maxSupply,
+            useProgramAuthority
+          )
           .accounts({
             authorityAccount: mainWallet.publicKey, // The Owner of the NFT
+            payer: payer.publicKey, // The Wallet paying the rent
             minterAccount: minterKeypair.publicKey, // The Minter Program
+            recipient, // The Wallet receiving the NFT
             tokenHolderAccount: tokenHolder, // Our NFT Token Holder Account Program
             metadataAccount: metadataAddress, // Our Metadata PDA
             masterEditionAccount: masterEditionAddress, // Our Master Edition PDA
+            programAuthority: getProgramAuthority(mainWallet.publicKey), // The Program PDA (only used with useProgramAuthority)
+            config: configAddress, // The Program Config PDA
+            treasury: treasuryAddress, // The Program Treasury PDA
+            paymentTokenAccount:
+              tokenPayment?.paymentTokenAccount ?? payer.publicKey, // Unused without a token price
+            treasuryTokenAccount:
+              tokenPayment?.treasuryTokenAccount ?? treasuryAddress, // Unused without a token price
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID, // The Token Metadata Program
           })
-          .signers([mainWallet.payer, minterKeypair]) // We bpth pass our main wallet and the minter program keypair
+          .signers([mainWallet.payer, payer.payer, minterKeypair]) // We pass our main wallet, the payer and the minter program keypair
           .rpc();
 
         return {
@@ -311,6 +386,182 @@ describe("r21_metaplex_tests", async () => {
           masterEditionAddress,
         };
       },
+      createCollection: async (
+        mainWallet: Wallet = wallet3,
+        useProgramAuthority = false
+      ) => {
+        const minterKeypair = anchor.web3.Keypair.generate();
+        const [metadataAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+            minterKeypair.publicKey.toBuffer(),
+          ],
+          TOKEN_METADATA_PROGRAM_ID
+        );
+        const [masterEditionAddress] =
+          anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              minterKeypair.publicKey.toBuffer(),
+              Buffer.from("edition"),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          );
+        const programAuthority = getProgramAuthority(mainWallet.publicKey);
+
+        // Minting the collection parent NFT
+        await program.methods
+          .createCollectionNft(
+            "TestCollection",
+            "TestNFT",
+            "https://arweave.net/TestCollection",
+            [
+              {
+                address: useProgramAuthority
+                  ? programAuthority
+                  : mainWallet.publicKey,
+                share: 100,
+              },
+            ],
+            0,
+            useProgramAuthority
+          )
+          .accounts({
+            authorityAccount: mainWallet.publicKey,
+            payer: mainWallet.publicKey,
+            minterAccount: minterKeypair.publicKey,
+            recipient: mainWallet.publicKey,
+            tokenHolderAccount: await getAssociatedTokenAddress(
+              minterKeypair.publicKey,
+              mainWallet.publicKey
+            ),
+            metadataAccount: metadataAddress,
+            masterEditionAccount: masterEditionAddress,
+            programAuthority,
+            config: configAddress,
+            treasury: treasuryAddress,
+            paymentTokenAccount: mainWallet.publicKey,
+            treasuryTokenAccount: treasuryAddress,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          })
+          .signers([mainWallet.payer, minterKeypair])
+          .rpc();
+
+        return { minterKeypair, metadataAddress, masterEditionAddress };
+      },
+      createNFTInCollection: async (
+        collection: {
+          minterKeypair: anchor.web3.Keypair;
+          metadataAddress: PublicKey;
+          masterEditionAddress: PublicKey;
+        },
+        mainWallet: Wallet = wallet3,
+        minterKeypair: anchor.web3.Keypair = anchor.web3.Keypair.generate(),
+        metadata = {
+          name: "TestNFT",
+          symbol: "TestNFT",
+          uri: "https://arweave.net/TestNFT",
+        },
+        creators = [{ address: mainWallet.publicKey, share: 100 }],
+        sellerFeeBasisPoints = 500,
+        maxSupply: anchor.BN | null = new anchor.BN(1), // null = unlimited prints
+        useProgramAuthority = false,
+        recipient: PublicKey = mainWallet.publicKey, // Wallet the NFT lands in
+        payer: Wallet = mainWallet // Wallet paying the rent, e.g. a relayer
+      ) => {
+        const tokenHolder = await getAssociatedTokenAddress(
+          minterKeypair.publicKey,
+          recipient
+        );
+        const metadataAddress = (
+          await anchor.web3.PublicKey.findProgramAddress(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              minterKeypair.publicKey.toBuffer(),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          )
+        )[0];
+        const masterEditionAddress = (
+          await anchor.web3.PublicKey.findProgramAddress(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              minterKeypair.publicKey.toBuffer(),
+              Buffer.from("edition"),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          )
+        )[0];
+
+        // Minting 1 NFT and verifying it against the collection
+        await program.methods
+          .createNftInCollection(
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+            creators,
+            sellerFeeBasisPoints,
+            maxSupply,
+            useProgramAuthority
+          )
+          .accounts({
+            nft: {
+              authorityAccount: mainWallet.publicKey,
+              payer: payer.publicKey,
+              minterAccount: minterKeypair.publicKey,
+              recipient,
+              tokenHolderAccount: tokenHolder,
+              metadataAccount: metadataAddress,
+              masterEditionAccount: masterEditionAddress,
+              programAuthority: getProgramAuthority(mainWallet.publicKey),
+              config: configAddress,
+              treasury: treasuryAddress,
+              paymentTokenAccount: mainWallet.publicKey,
+              treasuryTokenAccount: treasuryAddress,
+              tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            },
+            collectionMint: collection.minterKeypair.publicKey,
+            collectionMetadata: collection.metadataAddress,
+            collectionMasterEdition: collection.masterEditionAddress,
+          })
+          .signers([mainWallet.payer, payer.payer, minterKeypair])
+          .rpc();
+
+        return {
+          minterKeypair,
+          tokenHolder,
+          metadataAddress,
+          masterEditionAddress,
+        };
+      },
+      getListingAddresses: (mint: PublicKey) => ({
+        listing: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("listing"), mint.toBuffer()],
+          program.programId
+        )[0],
+        escrowTokenAccount: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("escrow"), mint.toBuffer()],
+          program.programId
+        )[0],
+      }),
+      getAuctionAddresses: (mint: PublicKey) => ({
+        auction: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("auction"), mint.toBuffer()],
+          program.programId
+        )[0],
+        escrowTokenAccount: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("auction_escrow"), mint.toBuffer()],
+          program.programId
+        )[0],
+        bidVault: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("bid_vault"), mint.toBuffer()],
+          program.programId
+        )[0],
+      }),
       getMetadata: async (metadataAddress) => {
         // get nft name and symbol
         const metadataAccount =
@@ -323,21 +574,39 @@ describe("r21_metaplex_tests", async () => {
         console.log("Name: ", metadata[0].data.name);
         console.log("Symbol: ", metadata[0].data.name);
         console.log("URI: ", metadata[0].data.uri);
+        return metadata[0];
       },
       updateNFT: async (
         mainWallet,
+        minterAddress,
         metadataAddress,
-        metadata = {
+        metadata: {
+          name?: string;
+          symbol?: string;
+          uri?: string;
+          sellerFeeBasisPoints?: number;
+          creators?: { address: PublicKey; share: number }[];
+        } = {
           name: "TestNFT",
           symbol: "TestNFT",
-          uri: "TestNFT",
+          uri: "https://arweave.net/TestNFT",
         }
       ) => {
+        // Fields left out are kept as they are on chain
         await program.methods
-          .updateNftMetadata(metadata.name, metadata.symbol, metadata.uri)
+          .updateNftMetadata(
+            metadata.name ?? null,
+            metadata.symbol ?? null,
+            metadata.uri ?? null,
+            metadata.sellerFeeBasisPoints ?? null,
+            metadata.creators ?? null
+          )
           .accounts({
             authorityAccount: mainWallet.publicKey,
+            minterAccount: minterAddress,
             metadataAccount: metadataAddress,
+            programAuthority: getProgramAuthority(mainWallet.publicKey), // Signs for program owned NFTs
+            config: configAddress,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           })
           .signers([mainWallet.payer])
@@ -349,25 +618,1190 @@ describe("r21_metaplex_tests", async () => {
     xit("can mint one NFT", async () => {
       await utils.createNFT();
     });
+    xit("rejects metadata with an invalid URI", async () => {
+      try {
+        await utils.createNFT(wallet3, undefined, {
+          name: "TestNFT",
+          symbol: "TestNFT",
+          uri: "TestNFT",
+        });
+      } catch (err) {
+        if (err.error?.errorCode?.code !== "InvalidMetadataUriScheme") {
+          throw err;
+        }
+        return;
+      }
+      throw new Error("NFT was created with an invalid URI");
+    });
+    xit("can mint one NFT with a royalty split", async () => {
+      const { metadataAddress } = await utils.createNFT(
+        wallet3,
+        undefined,
+        undefined,
+        [
+          { address: wallet3.publicKey, share: 70 },
+          { address: wallet2.publicKey, share: 30 },
+        ],
+        750
+      );
+
+      const metadata = await utils.getMetadata(metadataAddress);
+      if (metadata.data.sellerFeeBasisPoints !== 750) {
+        throw new Error("Royalty was not set");
+      }
+      if (
+        !metadata.data.creators?.[0].verified ||
+        metadata.data.creators?.[1].verified
+      ) {
+        throw new Error("Only the signing authority should be verified");
+      }
+    });
+    xit("can print numbered editions of an NFT", async () => {
+      const master = await utils.createNFT(
+        wallet3,
+        undefined,
+        undefined,
+        undefined,
+        undefined,
+        new anchor.BN(10)
+      );
+
+      for (const editionNumber of [1, 2]) {
+        const editionMinterKeypair = anchor.web3.Keypair.generate();
+        const [editionMetadataAddress] =
+          await anchor.web3.PublicKey.findProgramAddress(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              editionMinterKeypair.publicKey.toBuffer(),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          );
+        const [editionAddress] = await anchor.web3.PublicKey.findProgramAddress(
+          [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+            editionMinterKeypair.publicKey.toBuffer(),
+            Buffer.from("edition"),
+          ],
+          TOKEN_METADATA_PROGRAM_ID
+        );
+        // One marker account tracks 248 editions
+        const [editionMarkerAddress] =
+          await anchor.web3.PublicKey.findProgramAddress(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              master.minterKeypair.publicKey.toBuffer(),
+              Buffer.from("edition"),
+              Buffer.from(Math.floor(editionNumber / 248).toString()),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          );
+
+        await program.methods
+          .printEdition(new anchor.BN(editionNumber))
+          .accounts({
+            authorityAccount: wallet3.publicKey,
+            minterAccount: editionMinterKeypair.publicKey,
+            tokenHolderAccount: await getAssociatedTokenAddress(
+              editionMinterKeypair.publicKey,
+              wallet3.publicKey
+            ),
+            metadataAccount: editionMetadataAddress,
+            editionAccount: editionAddress,
+            editionMarkerAccount: editionMarkerAddress,
+            masterMinterAccount: master.minterKeypair.publicKey,
+            masterTokenHolderAccount: master.tokenHolder,
+            masterMetadataAccount: master.metadataAddress,
+            masterEditionAccount: master.masterEditionAddress,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          })
+          .signers([wallet3.payer, editionMinterKeypair])
+          .rpc();
+      }
+    });
+    xit("can burn an NFT and reclaim its rent", async () => {
+      const {
+        minterKeypair,
+        tokenHolder,
+        metadataAddress,
+        masterEditionAddress,
+      } = await utils.createNFT();
+
+      await program.methods
+        .burnNft()
+        .accounts({
+          authorityAccount: wallet3.publicKey,
+          minterAccount: minterKeypair.publicKey,
+          tokenHolderAccount: tokenHolder,
+          metadataAccount: metadataAddress,
+          editionAccount: masterEditionAddress,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      if (
+        (await program.provider.connection.getAccountInfo(metadataAddress)) !==
+        null
+      ) {
+        throw new Error("Metadata account was not closed");
+      }
+    });
+    xit("can mint one NFT owned by the program authority", async () => {
+      const programAuthority = getProgramAuthority(wallet3.publicKey);
+      const { metadataAddress, tokenHolder } = await utils.createNFT(
+        wallet3,
+        undefined,
+        undefined,
+        [{ address: programAuthority, share: 100 }],
+        500,
+        new anchor.BN(1),
+        true
+      );
+
+      const metadata = await utils.getMetadata(metadataAddress);
+      if (!metadata.updateAuthority.equals(programAuthority)) {
+        throw new Error("Update authority is not the program authority");
+      }
+      if (!metadata.data.creators?.[0]?.verified) {
+        throw new Error("Program authority was not verified as a creator");
+      }
+
+      const holder = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(tokenHolder)).data
+      );
+      if (!holder.owner.equals(wallet3.publicKey)) {
+        throw new Error("Token did not land in the signer's wallet");
+      }
+    });
+
+    xit("can withdraw the royalties collected by the program authority", async () => {
+      const programAuthority = getProgramAuthority(wallet3.publicKey);
+
+      // Stand in for royalties paid to the PDA as a creator
+      await program.provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: wallet.publicKey,
+            toPubkey: programAuthority,
+            lamports: 10_000_000,
+          })
+        )
+      );
+
+      await program.methods
+        .withdrawProgramAuthority()
+        .accounts({
+          authority: wallet3.publicKey,
+          programAuthority,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      if ((await provider.connection.getBalance(programAuthority)) !== 0) {
+        throw new Error("Program authority was not emptied");
+      }
+    });
+
+    xit("can gift an NFT to a wallet without a token account", async () => {
+      const { minterKeypair, tokenHolder, masterEditionAddress } =
+        await utils.createNFT();
+      const friend = anchor.web3.Keypair.generate();
+      const friendHolder = await getAssociatedTokenAddress(
+        minterKeypair.publicKey,
+        friend.publicKey
+      );
+
+      await program.methods
+        .transferNft(true)
+        .accounts({
+          authority: wallet3.publicKey,
+          mint: minterKeypair.publicKey,
+          sender: tokenHolder,
+          recipientWallet: friend.publicKey,
+          recipient: friendHolder,
+          payer: wallet3.publicKey,
+          editionAccount: masterEditionAddress,
+          config: configAddress,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      const { amount } = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(friendHolder)).data
+      );
+      if (Number(amount) !== 1) {
+        throw new Error("NFT did not reach the new wallet");
+      }
+      if ((await provider.connection.getAccountInfo(tokenHolder)) !== null) {
+        throw new Error("Sender token account was not closed");
+      }
+    });
+
+    xit("can mint one NFT straight into another wallet", async () => {
+      const customer = anchor.web3.Keypair.generate();
+      const { metadataAddress, tokenHolder } = await utils.createNFT(
+        wallet3,
+        undefined,
+        undefined,
+        undefined,
+        500,
+        new anchor.BN(1),
+        false,
+        customer.publicKey
+      );
+
+      const holder = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(tokenHolder)).data
+      );
+      if (!holder.owner.equals(customer.publicKey)) {
+        throw new Error("Token did not land in the customer's wallet");
+      }
+
+      const metadata = await utils.getMetadata(metadataAddress);
+      if (!metadata.updateAuthority.equals(wallet3.publicKey)) {
+        throw new Error("Update authority did not stay with the creator");
+      }
+    });
+
+    xit("can mint one NFT with a relayer paying the rent", async () => {
+      const before = await provider.connection.getBalance(wallet3.publicKey);
+      const { tokenHolder } = await utils.createNFT(
+        wallet3,
+        undefined,
+        undefined,
+        undefined,
+        500,
+        new anchor.BN(1),
+        false,
+        wallet3.publicKey,
+        wallet2
+      );
+
+      if ((await provider.connection.getBalance(wallet3.publicKey)) !== before) {
+        throw new Error("The authority paid for the mint");
+      }
+      const { amount } = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(tokenHolder)).data
+      );
+      if (Number(amount) !== 1) {
+        throw new Error("NFT was not minted");
+      }
+    });
+
+    xit("emits an event when an NFT is created", async () => {
+      let event = null;
+      const listener = program.addEventListener("NFTCreated", (e) => {
+        event = e;
+      });
+
+      const { minterKeypair } = await utils.createNFT();
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      if (
+        !event?.mint.equals(minterKeypair.publicKey) ||
+        !event?.owner.equals(wallet3.publicKey) ||
+        event?.uri !== "https://arweave.net/TestNFT"
+      ) {
+        throw new Error("NFTCreated event is missing or wrong");
+      }
+    });
+
+    xit("can mint a full batch of NFTs in one instruction", async () => {
+      // MAX_BATCH_SIZE, more than a legacy transaction fits, so it goes through a lookup table
+      const batchId = new anchor.BN(Date.now());
+      const minters = [...Array(10).keys()].map((i) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("batch_mint"),
+            wallet3.publicKey.toBuffer(),
+            batchId.toArrayLike(Buffer, "le", 8),
+            Buffer.from([i]),
+          ],
+          program.programId
+        )
+      );
+      const remainingAccounts = [];
+      for (const [minter] of minters) {
+        const [metadataAddress] = await anchor.web3.PublicKey.findProgramAddress(
+          [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+            minter.toBuffer(),
+          ],
+          TOKEN_METADATA_PROGRAM_ID
+        );
+        const [masterEditionAddress] =
+          await anchor.web3.PublicKey.findProgramAddress(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              minter.toBuffer(),
+              Buffer.from("edition"),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          );
+        remainingAccounts.push(
+          { pubkey: minter, isSigner: false, isWritable: true },
+          {
+            pubkey: await getAssociatedTokenAddress(minter, wallet3.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: metadataAddress, isSigner: false, isWritable: true },
+          { pubkey: masterEditionAddress, isSigner: false, isWritable: true }
+        );
+      }
+
+      const batchInstruction = await program.methods
+        .createNftBatch(
+          batchId,
+          minters.map(([_, bump], i) => ({
+            title: `TestNFT #${i}`,
+            symbol: "TestNFT",
+            uri: `https://arweave.net/TestNFT${i}`,
+            mintBump: bump,
+          })),
+          [{ address: wallet3.publicKey, share: 100 }],
+          500,
+          new anchor.BN(1),
+          false
+        )
+        .accounts({
+          authorityAccount: wallet3.publicKey,
+          payer: wallet3.publicKey,
+          recipient: wallet3.publicKey,
+          programAuthority: getProgramAuthority(wallet3.publicKey),
+          config: configAddress,
+          treasury: treasuryAddress,
+          paymentTokenAccount: wallet3.publicKey,
+          treasuryTokenAccount: treasuryAddress,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+
+      // Put every account of the batch in a lookup table, a few at a time to stay below the tx size
+      const [createTableInstruction, lookupTable] =
+        anchor.web3.AddressLookupTableProgram.createLookupTable({
+          authority: wallet.publicKey,
+          payer: wallet.publicKey,
+          recentSlot: await provider.connection.getSlot(),
+        });
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(createTableInstruction)
+      );
+      const addresses = [
+        ...new Set(
+          batchInstruction.keys.map(({ pubkey }) => pubkey.toBase58())
+        ),
+      ].map((address) => new PublicKey(address));
+      for (let i = 0; i < addresses.length; i += 20) {
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            anchor.web3.AddressLookupTableProgram.extendLookupTable({
+              lookupTable,
+              authority: wallet.publicKey,
+              payer: wallet.publicKey,
+              addresses: addresses.slice(i, i + 20),
+            })
+          )
+        );
+      }
+      // The new addresses can only be used from the next slot on
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      const lookupTableAccount = (
+        await provider.connection.getAddressLookupTable(lookupTable)
+      ).value;
+
+      const { blockhash, lastValidBlockHeight } =
+        await provider.connection.getLatestBlockhash();
+      const transaction = new anchor.web3.VersionedTransaction(
+        new anchor.web3.TransactionMessage({
+          payerKey: wallet3.publicKey,
+          recentBlockhash: blockhash,
+          instructions: [
+            // A full batch takes far more than the default 200k compute units
+            anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+              units: 1_400_000,
+            }),
+            batchInstruction,
+          ],
+        }).compileToV0Message([lookupTableAccount])
+      );
+      // Only the authority signs, the mints are PDAs the program signs for
+      transaction.sign([wallet3.payer]);
+      await provider.connection.confirmTransaction({
+        signature: await provider.connection.sendTransaction(transaction),
+        blockhash,
+        lastValidBlockHeight,
+      });
+
+      for (const [minter] of minters) {
+        const mint = MintLayout.decode(
+          (await provider.connection.getAccountInfo(minter)).data
+        );
+        if (Number(mint.supply) !== 1) {
+          throw new Error("Batch NFT was not minted");
+        }
+      }
+    });
+
+    xit("can list an NFT and sell it from escrow", async () => {
+      const PRICE = 100_000_000;
+      const creator = anchor.web3.Keypair.generate();
+      const { minterKeypair, tokenHolder, metadataAddress } =
+        await utils.createNFT(
+          wallet3,
+          undefined,
+          undefined,
+          [
+            { address: wallet3.publicKey, share: 80 },
+            { address: creator.publicKey, share: 20 },
+          ],
+          1000 // 10% royalty
+        );
+      const mint = minterKeypair.publicKey;
+      const { listing, escrowTokenAccount } = utils.getListingAddresses(mint);
+
+      await program.methods
+        .listNft(new anchor.BN(PRICE))
+        .accounts({
+          seller: wallet3.publicKey,
+          mint,
+          sellerTokenAccount: tokenHolder,
+          listing,
+          escrowTokenAccount,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      const sellerBalance = await provider.connection.getBalance(
+        wallet3.publicKey
+      );
+      const buyerTokenAccount = await getAssociatedTokenAddress(
+        mint,
+        wallet2.publicKey
+      );
+      await program.methods
+        .buyNft()
+        .accounts({
+          buyer: wallet2.publicKey,
+          seller: wallet3.publicKey,
+          mint,
+          metadataAccount: metadataAddress,
+          listing,
+          escrowTokenAccount,
+          buyerTokenAccount,
+        })
+        // Every creator, in the same order as the metadata
+        .remainingAccounts([
+          { pubkey: wallet3.publicKey, isSigner: false, isWritable: true },
+          { pubkey: creator.publicKey, isSigner: false, isWritable: true },
+        ])
+        .signers([wallet2.payer])
+        .rpc();
+
+      const { amount } = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(buyerTokenAccount)).data
+      );
+      if (Number(amount) !== 1) {
+        throw new Error("Buyer did not receive the NFT");
+      }
+      // The seller is also the 80% creator, so only the other creator's 2% is missing
+      if (
+        (await provider.connection.getBalance(wallet3.publicKey)) <
+        sellerBalance + PRICE * 0.98
+      ) {
+        throw new Error("Seller was not paid");
+      }
+      if (
+        (await provider.connection.getBalance(creator.publicKey)) !==
+        PRICE * 0.02
+      ) {
+        throw new Error("Creator royalty was not paid");
+      }
+      if ((await provider.connection.getAccountInfo(listing)) !== null) {
+        throw new Error("Listing was not closed");
+      }
+    });
+
+    xit("can sell an NFT whose royalty is too small for an empty creator wallet", async () => {
+      const PRICE = 1_000_000;
+      const creator = anchor.web3.Keypair.generate();
+      const { minterKeypair, tokenHolder, metadataAddress } =
+        await utils.createNFT(
+          wallet3,
+          undefined,
+          undefined,
+          [
+            { address: wallet3.publicKey, share: 80 },
+            { address: creator.publicKey, share: 20 },
+          ],
+          1000 // 10% royalty, the creator's 20,000 lamports are below rent exempt
+        );
+      const mint = minterKeypair.publicKey;
+      const { listing, escrowTokenAccount } = utils.getListingAddresses(mint);
+
+      await program.methods
+        .listNft(new anchor.BN(PRICE))
+        .accounts({
+          seller: wallet3.publicKey,
+          mint,
+          sellerTokenAccount: tokenHolder,
+          listing,
+          escrowTokenAccount,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      await program.methods
+        .buyNft()
+        .accounts({
+          buyer: wallet2.publicKey,
+          seller: wallet3.publicKey,
+          mint,
+          metadataAccount: metadataAddress,
+          listing,
+          escrowTokenAccount,
+          buyerTokenAccount: await getAssociatedTokenAddress(
+            mint,
+            wallet2.publicKey
+          ),
+        })
+        .remainingAccounts([
+          { pubkey: wallet3.publicKey, isSigner: false, isWritable: true },
+          { pubkey: creator.publicKey, isSigner: false, isWritable: true },
+        ])
+        .signers([wallet2.payer])
+        .rpc();
+
+      // The share went to the seller instead of failing the sale
+      if ((await provider.connection.getBalance(creator.publicKey)) !== 0) {
+        throw new Error("Creator wallet was left below rent exempt");
+      }
+    });
+
+    xit("can list an NFT without moving it out of the wallet", async () => {
+      const PRICE = 100_000_000;
+      const { minterKeypair, tokenHolder, metadataAddress, masterEditionAddress } =
+        await utils.createNFT();
+      const mint = minterKeypair.publicKey;
+      const { listing } = utils.getListingAddresses(mint);
+
+      await program.methods
+        .listNftDelegated(new anchor.BN(PRICE))
+        .accounts({
+          seller: wallet3.publicKey,
+          mint,
+          editionAccount: masterEditionAddress,
+          sellerTokenAccount: tokenHolder,
+          listing,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      const listed = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(tokenHolder)).data
+      );
+      if (Number(listed.amount) !== 1 || listed.state !== 2) {
+        throw new Error("NFT did not stay frozen in the seller's wallet");
+      }
+
+      const buyerTokenAccount = await getAssociatedTokenAddress(
+        mint,
+        wallet2.publicKey
+      );
+      await program.methods
+        .buyNftDelegated()
+        .accounts({
+          buyer: wallet2.publicKey,
+          seller: wallet3.publicKey,
+          mint,
+          metadataAccount: metadataAddress,
+          editionAccount: masterEditionAddress,
+          listing,
+          sellerTokenAccount: tokenHolder,
+          buyerTokenAccount,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: wallet3.publicKey, isSigner: false, isWritable: true },
+        ])
+        .signers([wallet2.payer])
+        .rpc();
+
+      const { amount } = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(buyerTokenAccount)).data
+      );
+      if (Number(amount) !== 1) {
+        throw new Error("Buyer did not receive the NFT");
+      }
+    });
+
+    xit("can auction an NFT to the highest bidder", async () => {
+      const RESERVE = 50_000_000;
+      const { minterKeypair, tokenHolder, metadataAddress } =
+        await utils.createNFT();
+      const mint = minterKeypair.publicKey;
+      const { auction, escrowTokenAccount, bidVault } =
+        utils.getAuctionAddresses(mint);
+
+      await program.methods
+        .createAuction(
+          new anchor.BN(RESERVE),
+          new anchor.BN(10_000_000),
+          new anchor.BN(5), // seconds
+          new anchor.BN(2) // anti-sniping window
+        )
+        .accounts({
+          seller: wallet3.publicKey,
+          mint,
+          sellerTokenAccount: tokenHolder,
+          auction,
+          escrowTokenAccount,
+          bidVault,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      await program.methods
+        .placeBid(new anchor.BN(RESERVE))
+        .accounts({
+          bidder: wallet2.publicKey,
+          mint,
+          auction,
+          bidVault,
+          previousBidder: wallet2.publicKey, // Nobody to refund yet
+        })
+        .signers([wallet2.payer])
+        .rpc();
+
+      // Wait for the auction, and any extension, to run out
+      await new Promise((resolve) => setTimeout(resolve, 8000));
+
+      const winnerTokenAccount = await getAssociatedTokenAddress(
+        mint,
+        wallet2.publicKey
+      );
+      await program.methods
+        .settleAuction()
+        .accounts({
+          payer: wallet.publicKey,
+          seller: wallet3.publicKey,
+          winner: wallet2.publicKey,
+          mint,
+          metadataAccount: metadataAddress,
+          auction,
+          escrowTokenAccount,
+          bidVault,
+          winnerTokenAccount,
+        })
+        .remainingAccounts([
+          { pubkey: wallet3.publicKey, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      const { amount } = AccountLayout.decode(
+        (await provider.connection.getAccountInfo(winnerTokenAccount)).data
+      );
+      if (Number(amount) !== 1) {
+        throw new Error("Winner did not receive the NFT");
+      }
+      if ((await provider.connection.getAccountInfo(auction)) !== null) {
+        throw new Error("Auction was not closed");
+      }
+    });
+
+    xit("refunds early dutch sale buyers down to the clearing price", async () => {
+      const saleId = new anchor.BN(Date.now());
+      const [sale] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("dutch_sale"),
+          wallet3.publicKey.toBuffer(),
+          saleId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [saleVault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("dutch_vault"), sale.toBuffer()],
+        program.programId
+      );
+      const [receipt] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("dutch_receipt"), sale.toBuffer(), wallet2.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createDutchSale(
+          saleId,
+          new anchor.BN(200_000_000), // Start price
+          new anchor.BN(100_000_000), // Floor price
+          new anchor.BN(Math.floor(Date.now() / 1000) - 1), // Start time
+          new anchor.BN(50_000_000), // Price drop
+          new anchor.BN(2), // Every 2 seconds
+          2, // Max items
+          true, // Rebate
+          "TestDrop",
+          "TestNFT",
+          "https://arweave.net/TestDrop",
+          [{ address: wallet3.publicKey, share: 100 }],
+          500
+        )
+        .accounts({
+          authority: wallet3.publicKey,
+          sale,
+          saleVault,
+          config: configAddress,
+        })
+        .signers([wallet3.payer])
+        .rpc();
+
+      const buy = async () => {
+        const minterKeypair = anchor.web3.Keypair.generate();
+        const [metadataAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+            minterKeypair.publicKey.toBuffer(),
+          ],
+          TOKEN_METADATA_PROGRAM_ID
+        );
+        const [masterEditionAddress] =
+          anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              minterKeypair.publicKey.toBuffer(),
+              Buffer.from("edition"),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          );
+        await program.methods
+          .buyDutchSale()
+          .accounts({
+            buyer: wallet2.publicKey,
+            authority: wallet3.publicKey,
+            minterAccount: minterKeypair.publicKey,
+            tokenHolderAccount: await getAssociatedTokenAddress(
+              minterKeypair.publicKey,
+              wallet2.publicKey
+            ),
+            metadataAccount: metadataAddress,
+            masterEditionAccount: masterEditionAddress,
+            sale,
+            saleVault,
+            receipt,
+            programAuthority: getProgramAuthority(wallet3.publicKey), // Derived from the seller
+            config: configAddress,
+            treasury: treasuryAddress,
+            paymentTokenAccount: wallet2.publicKey, // Unused without a token price
+            treasuryTokenAccount: treasuryAddress, // Unused without a token price
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          })
+          .signers([wallet2.payer, minterKeypair])
+          .rpc();
+      };
+
+      await buy();
+      // Let the price drop before the second, and last, item
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+      await buy();
+
+      // The buyer does not have to sign, the seller can close out receipts nobody claims
+      const buyerBalance = await provider.connection.getBalance(
+        wallet2.publicKey
+      );
+      await program.methods
+        .claimDutchRebate()
+        .accounts({
+          buyer: wallet2.publicKey,
+          sale,
+          saleVault,
+          receipt,
+        })
+        .rpc();
+
+      const { lastPrice } = await program.account.dutchSale.fetch(sale);
+      if (lastPrice.toNumber() >= 200_000_000) {
+        throw new Error("Price did not drop");
+      }
+      if (
+        (await provider.connection.getBalance(wallet2.publicKey)) <=
+        buyerBalance
+      ) {
+        throw new Error("Rebate was not paid");
+      }
+      if ((await provider.connection.getAccountInfo(receipt)) !== null) {
+        throw new Error("Receipt was not closed");
+      }
+
+      // Pays the seller the clearing price, then gives back the rent once nobody can claim anymore
+      await program.methods
+        .endDutchSale()
+        .accounts({ authority: wallet3.publicKey, sale, saleVault })
+        .signers([wallet3.payer])
+        .rpc();
+      await program.methods
+        .closeDutchSale()
+        .accounts({ authority: wallet3.publicKey, sale, saleVault })
+        .signers([wallet3.payer])
+        .rpc();
+      if (
+        (await provider.connection.getAccountInfo(sale)) !== null ||
+        (await provider.connection.getBalance(saleVault)) !== 0
+      ) {
+        throw new Error("Sale was not closed");
+      }
+    });
+
+    xit("can mint one NFT into a collection", async () => {
+      const collection = await utils.createCollection();
+      const { metadataAddress } = await utils.createNFTInCollection(collection);
+
+      const metadata = await utils.getMetadata(metadataAddress);
+      if (
+        !metadata.collection?.verified ||
+        !metadata.collection.key.equals(collection.minterKeypair.publicKey)
+      ) {
+        throw new Error("NFT is not a verified member of the collection");
+      }
+    });
+
+    xit("can mint one NFT into a collection with a relayer paying the rent", async () => {
+      const collection = await utils.createCollection();
+      const authorityBalance = await provider.connection.getBalance(
+        wallet3.publicKey
+      );
+
+      const { metadataAddress } = await utils.createNFTInCollection(
+        collection,
+        wallet3,
+        undefined,
+        undefined,
+        undefined,
+        500,
+        new anchor.BN(1),
+        false,
+        wallet3.publicKey,
+        wallet2 // The relayer
+      );
+
+      if (!(await utils.getMetadata(metadataAddress)).collection?.verified) {
+        throw new Error("NFT is not a verified member of the collection");
+      }
+      if (
+        (await provider.connection.getBalance(wallet3.publicKey)) !==
+        authorityBalance
+      ) {
+        throw new Error("Authority paid for the mint");
+      }
+    });
+
+    xit("can collect and update NFTs owned by the program authority", async () => {
+      const programAuthority = getProgramAuthority(wallet3.publicKey);
+      const collection = await utils.createCollection(wallet3, true);
+
+      const { minterKeypair, metadataAddress } =
+        await utils.createNFTInCollection(
+          collection,
+          wallet3,
+          undefined,
+          undefined,
+          [{ address: programAuthority, share: 100 }],
+          500,
+          new anchor.BN(1),
+          true
+        );
+      if (!(await utils.getMetadata(metadataAddress)).collection?.verified) {
+        throw new Error("NFT is not a verified member of the collection");
+      }
+
+      // The program signs the update with the PDA of the signer
+      await utils.updateNFT(wallet3, minterKeypair.publicKey, metadataAddress, {
+        name: "Renamed",
+      });
+      const metadata = await utils.getMetadata(metadataAddress);
+      if (
+        metadata.data.name.replace(/\0/g, "") !== "Renamed" ||
+        !metadata.updateAuthority.equals(programAuthority)
+      ) {
+        throw new Error("Program authority did not update the NFT");
+      }
+    });
     xit("can mint one NFT and update metadata", async () => {
-      const { metadataAddress } = await utils.createNFT();
+      const { minterKeypair, metadataAddress } = await utils.createNFT();
       await utils.getMetadata(metadataAddress);
 
       // Update the metadata
-      await utils.updateNFT(wallet3, metadataAddress, {
+      await utils.updateNFT(wallet3, minterKeypair.publicKey, metadataAddress, {
         name: "NewName",
         symbol: "NewSymbol",
-        uri: "NewURI",
+        uri: "https://arweave.net/NewURI",
       });
       await utils.getMetadata(metadataAddress);
 
       // Update the metadata
-      await utils.updateNFT(wallet3, metadataAddress, {
+      await utils.updateNFT(wallet3, minterKeypair.publicKey, metadataAddress, {
         name: "NewName2",
         symbol: "NewSymbol2",
-        uri: "NewURI2",
+        uri: "https://arweave.net/NewURI2",
       });
       await utils.getMetadata(metadataAddress);
     });
+    xit("can rename an NFT without touching its royalties", async () => {
+      const { minterKeypair, metadataAddress } = await utils.createNFT();
+
+      await utils.updateNFT(wallet3, minterKeypair.publicKey, metadataAddress, {
+        name: "Renamed",
+      });
+
+      const metadata = await utils.getMetadata(metadataAddress);
+      if (
+        metadata.data.sellerFeeBasisPoints !== 500 ||
+        !metadata.data.creators?.[0].address.equals(wallet3.publicKey)
+      ) {
+        throw new Error("Royalties were overwritten by the rename");
+      }
+    });
+
+    // The config is a single global account that can't be closed again, so these run after
+    // every other test. Mocha runs nested suites after the tests of their parent.
+    describe("with a program config", () => {
+      // Every test starts from the same config: wallet2 is the guardian, wallet3 the only
+      // allowed minter and the provider wallet the admin
+      beforeEach(async () => {
+        if ((await provider.connection.getAccountInfo(configAddress)) === null) {
+          await program.methods
+            .initializeConfig(
+              wallet2.publicKey,
+              [wallet3.publicKey],
+              250,
+              [{ address: wallet3.publicKey, share: 100 }],
+              "CFG",
+              new anchor.BN(0)
+            )
+            .accounts({
+              // Only the upgrade authority can create the config, so the program has to be
+              // deployed as upgradeable by the provider wallet (anchor deploy)
+              admin: wallet.publicKey,
+              config: configAddress,
+              treasury: treasuryAddress,
+              program: program.programId,
+              programData,
+            })
+            .rpc();
+        } else {
+          await program.methods
+            .updateConfig(
+              null,
+              wallet2.publicKey,
+              [wallet3.publicKey],
+              250,
+              [{ address: wallet3.publicKey, share: 100 }],
+              "CFG",
+              new anchor.BN(0)
+            )
+            .accounts({ admin: wallet.publicKey, config: configAddress })
+            .rpc();
+        }
+      });
+
+      after(async () => {
+        // Leave nothing behind that charges or blocks a mint
+        await program.methods
+          .setPaused(0)
+          .accounts({ authority: wallet.publicKey, config: configAddress })
+          .rpc();
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, new anchor.BN(0))
+          .accounts({ admin: wallet.publicKey, config: configAddress })
+          .rpc();
+        await program.methods
+          .setTokenPrice(null, new anchor.BN(0))
+          .accounts({ admin: wallet.publicKey, config: configAddress })
+          .rpc();
+      });
+
+      xit("only lets allowed minters mint", async () => {
+        // Empty symbol and creators fall back to the config defaults
+        const { metadataAddress } = await utils.createNFT(
+          wallet3,
+          undefined,
+          { name: "TestNFT", symbol: "", uri: "https://arweave.net/TestNFT" },
+          []
+        );
+        const metadata = await utils.getMetadata(metadataAddress);
+        if (
+          metadata.data.symbol.replace(/\0/g, "") !== "CFG" ||
+          metadata.data.sellerFeeBasisPoints !== 250
+        ) {
+          throw new Error("Config defaults were not applied");
+        }
+
+        try {
+          await utils.createNFT(wallet2);
+          throw new Error("Minted without being allowed");
+        } catch (e) {
+          if (!`${e}`.includes("MinterNotAllowed")) throw e;
+        }
+
+        await program.methods
+          .updateConfig(
+            null,
+            null,
+            [wallet3.publicKey, wallet2.publicKey],
+            null,
+            null,
+            null,
+            null
+          )
+          .accounts({ admin: wallet.publicKey, config: configAddress })
+          .rpc();
+        await utils.createNFT(wallet2);
+      });
+
+      xit("lets the guardian pause and only the admin resume minting", async () => {
+        const PAUSE_CREATE = 1 << 0;
+
+        await program.methods
+          .setPaused(PAUSE_CREATE)
+          .accounts({ authority: wallet2.publicKey, config: configAddress })
+          .signers([wallet2.payer])
+          .rpc();
+
+        try {
+          await utils.createNFT();
+          throw new Error("Minted while paused");
+        } catch (e) {
+          if (!`${e}`.includes("InstructionPaused")) throw e;
+        }
+
+        // The guardian can't clear bits, minting stays paused
+        await program.methods
+          .setPaused(0)
+          .accounts({ authority: wallet2.publicKey, config: configAddress })
+          .signers([wallet2.payer])
+          .rpc();
+        try {
+          await utils.createNFT();
+          throw new Error("Guardian resumed minting");
+        } catch (e) {
+          if (!`${e}`.includes("InstructionPaused")) throw e;
+        }
+
+        await program.methods
+          .setPaused(0)
+          .accounts({ authority: wallet.publicKey, config: configAddress })
+          .rpc();
+        await utils.createNFT();
+      });
+
+      xit("charges the mint price into the treasury", async () => {
+        const MINT_PRICE = 10_000_000;
+
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, new anchor.BN(MINT_PRICE))
+          .accounts({ admin: wallet.publicKey, config: configAddress })
+          .rpc();
+
+        const before = await provider.connection.getBalance(treasuryAddress);
+        await utils.createNFT();
+        const after = await provider.connection.getBalance(treasuryAddress);
+        if (after - before !== MINT_PRICE) {
+          throw new Error("Mint price was not collected");
+        }
+
+        await program.methods
+          .withdrawTreasury(new anchor.BN(MINT_PRICE))
+          .accounts({
+            admin: wallet.publicKey,
+            config: configAddress,
+            treasury: treasuryAddress,
+            destination: wallet.publicKey,
+          })
+          .rpc();
+        if ((await provider.connection.getBalance(treasuryAddress)) !== before) {
+          throw new Error("Treasury was not withdrawn");
+        }
+
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, new anchor.BN(0))
+          .accounts({ admin: wallet.publicKey, config: configAddress })
+          .rpc();
+      });
+
+      xit("charges the token price into the treasury token account", async () => {
+        const TOKEN_PRICE = 5_000_000;
+
+        // A stand in for a stablecoin
+        const priceMint = await createMint(
+          provider.connection,
+          wallet3.payer,
+          wallet3.publicKey,
+          null,
+          6
+        );
+        const paymentTokenAccount = await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          wallet3.payer,
+          priceMint,
+          wallet3.publicKey
+        );
+        const treasuryTokenAccount = await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          wallet3.payer,
+          priceMint,
+          treasuryAddress,
+          true
+        );
+        await mintTo(
+          provider.connection,
+          wallet3.payer,
+          priceMint,
+          paymentTokenAccount.address,
+          wallet3.publicKey,
+          TOKEN_PRICE
+        );
+
+        await program.methods
+          .setTokenPrice(priceMint, new anchor.BN(TOKEN_PRICE))
+          .accounts({ admin: wallet.publicKey, config: configAddress })
+          .rpc();
+
+        await utils.createNFT(
+          wallet3,
+          undefined,
+          undefined,
+          undefined,
+          500,
+          new anchor.BN(1),
+          false,
+          wallet3.publicKey,
+          wallet3,
+          {
+            paymentTokenAccount: paymentTokenAccount.address,
+            treasuryTokenAccount: treasuryTokenAccount.address,
+          }
+        );
+
+        const { amount } = AccountLayout.decode(
+          (await provider.connection.getAccountInfo(treasuryTokenAccount.address))
+            .data
+        );
+        if (Number(amount) !== TOKEN_PRICE) {
+          throw new Error("Token price was not collected");
+        }
+
+        await program.methods
+          .setTokenPrice(null, new anchor.BN(0))
+          .accounts({ admin: wallet.publicKey, config: configAddress })
+          .rpc();
+      });
+    });
   });
 });